@@ -0,0 +1,189 @@
+use crate::pattern::{Pattern, PatternError};
+use crate::plane::{find_motion, normalize};
+use crate::{Cell, Rule};
+
+const DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+fn digit_value(c: char) -> Option<u32> {
+    c.to_digit(36)
+}
+// Extended Wechsler format: 5-row strips separated by 'z', one base-32 digit
+// per column with bit k for row k of the strip, and runs of blank columns
+// shortened to 'w' (2), 'x' (3) or 'y' plus a digit (4 to 39).
+fn wechsler(cells: &[(i64, i64)]) -> String {
+    let cells = normalize(cells);
+    let height = cells.iter().map(|&(row, _)| row + 1).max().unwrap_or(0);
+    let width = cells.iter().map(|&(_, col)| col + 1).max().unwrap_or(0);
+    let mut strips = vec![vec![0u8; width as usize]; ((height + 4) / 5).max(1) as usize];
+    for &(row, col) in cells.iter() {
+        strips[(row / 5) as usize][col as usize] |= 1 << (row % 5);
+    }
+    let mut code = String::new();
+    for (i, strip) in strips.iter().enumerate() {
+        if i > 0 {
+            code.push('z');
+        }
+        let end = strip
+            .iter()
+            .rposition(|&bits| bits != 0)
+            .map_or(0, |last| last + 1);
+        let mut blanks = 0;
+        for &bits in strip[..end].iter() {
+            if bits == 0 {
+                blanks += 1;
+                continue;
+            }
+            while blanks >= 40 {
+                code.push_str("yz");
+                blanks -= 39;
+            }
+            match blanks {
+                0 => {}
+                1 => code.push('0'),
+                2 => code.push('w'),
+                3 => code.push('x'),
+                _ => {
+                    code.push('y');
+                    code.push(DIGITS[blanks - 4] as char);
+                }
+            }
+            blanks = 0;
+            code.push(DIGITS[bits as usize] as char);
+        }
+    }
+    if code.is_empty() {
+        code.push('0');
+    }
+    code
+}
+// the `k`th of the eight rotations and reflections of (row, column)
+fn orient(k: u8, row: i64, col: i64) -> (i64, i64) {
+    let (row, col) = if k & 4 != 0 { (col, row) } else { (row, col) };
+    let row = if k & 2 != 0 { -row } else { row };
+    let col = if k & 1 != 0 { -col } else { col };
+    (row, col)
+}
+// Shortest, then alphabetically first, code over all eight orientations.
+fn canonical_wechsler(cells: &[(i64, i64)]) -> String {
+    (0..8)
+        .map(|k| {
+            let oriented: Vec<(i64, i64)> = cells
+                .iter()
+                .map(|&(row, col)| orient(k, row, col))
+                .collect();
+            wechsler(&oriented)
+        })
+        .min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))
+        .unwrap_or_else(|| "0".to_string())
+}
+// Runs `cells` in isolation on an unbounded plane under `rule` until it
+// repeats its first generation, and names it "xs<population>_" for a still
+// life, "xp<period>_" for an oscillator or "xq<period>_" for a spaceship
+// followed by the canonical code over all phases.
+pub(crate) fn identify(
+    rule: &Rule,
+    cells: &[(i64, i64)],
+    max_period: u32,
+) -> Result<String, PatternError> {
+    let motion = find_motion(rule, cells, max_period)?;
+    let code = motion
+        .phases
+        .iter()
+        .map(|phase| canonical_wechsler(phase))
+        .min_by(|a, b| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))
+        .unwrap_or_default();
+    Ok(match (motion.period, motion.displacement) {
+        (1, (0, 0)) => format!("xs{}_{}", cells.len(), code),
+        (period, (0, 0)) => format!("xp{}_{}", period, code),
+        (period, _) => format!("xq{}_{}", period, code),
+    })
+}
+// Whether `text` is a single "xs4_33"-style apgcode with its prefix.
+pub(crate) fn looks_like(text: &str) -> bool {
+    match text.split_once('_') {
+        Some((prefix, body)) => {
+            let mut prefix = prefix.chars();
+            prefix.next() == Some('x')
+                && prefix.next().is_some_and(|kind| "spq".contains(kind))
+                && prefix.as_str().parse::<u32>().is_ok()
+                && !body.is_empty()
+                && body.chars().all(|c| c.is_ascii_alphanumeric())
+        }
+        None => false,
+    }
+}
+// Decodes "xs4_33"-style apgcodes, or a bare extended Wechsler code, into its
+// first phase.
+pub(crate) fn decode(code: &str) -> Result<Pattern, PatternError> {
+    let invalid = || PatternError::InvalidApgcode(code.to_string());
+    let code = code.trim();
+    let body = match code.split_once('_') {
+        Some((prefix, body)) if prefix.starts_with('x') => body,
+        Some(_) => return Err(invalid()),
+        None => code,
+    };
+    let mut cells = Vec::new();
+    let (mut strip, mut col) = (0i64, 0i64);
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            'w' => col += 2,
+            'x' => col += 3,
+            // the digit after 'y' can be 'z' itself, for 39 blanks
+            'y' => {
+                let run = chars.next().and_then(digit_value).ok_or_else(invalid)?;
+                col += 4 + run as i64;
+            }
+            'z' => {
+                strip += 1;
+                col = 0;
+            }
+            _ => {
+                let bits = digit_value(c)
+                    .filter(|&bits| bits < 32)
+                    .ok_or_else(invalid)?;
+                for row in (0..5).filter(|row| bits & 1 << row != 0) {
+                    cells.push((strip * 5 + row, col, 1));
+                }
+                col += 1;
+            }
+        }
+    }
+    Ok(Pattern::from_coordinates(&cells))
+}
+// (row, column) of every live cell of `pattern`
+pub(crate) fn live_cells(pattern: &Pattern) -> Vec<(i64, i64)> {
+    pattern
+        .cells
+        .iter()
+        .filter(|&&(_, _, state)| state == Cell::Alive as u8)
+        .map(|&(row, col, _)| (row as i64, col as i64))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // every column of a decoded pattern, relative to its first
+    fn columns(code: &str) -> Vec<i64> {
+        let pattern = decode(code).unwrap();
+        let mut columns: Vec<i64> = pattern
+            .cells
+            .iter()
+            .map(|&(_, col, _)| col as i64)
+            .collect();
+        columns.sort();
+        columns
+    }
+
+    #[test]
+    fn round_trips_long_gaps() {
+        for gap in [38, 39, 40, 78, 79, 80] {
+            let cells = [(0, 0), (0, gap + 1)];
+            let code = wechsler(&cells);
+            assert_eq!(columns(&code), vec![0, gap + 1], "gap {} as {}", gap, code);
+        }
+        assert_eq!(wechsler(&[(0, 0), (0, 40)]), "1yz1");
+    }
+}
@@ -0,0 +1,48 @@
+use wasm_bindgen::prelude::*;
+
+// Opt-in saving of share strings to localStorage under `key`, at most once
+// every `interval` milliseconds.
+#[derive(Clone, Debug)]
+pub(crate) struct Autosave {
+    pub(crate) key: String,
+    interval: f64,
+    saved_at: f64,
+}
+impl Autosave {
+    pub(crate) fn new(key: &str, interval: u32) -> Autosave {
+        Autosave {
+            key: key.to_string(),
+            interval: interval as f64,
+            saved_at: f64::NEG_INFINITY,
+        }
+    }
+    pub(crate) fn is_due(&self) -> bool {
+        js_sys::Date::now() - self.saved_at >= self.interval
+    }
+    // Restarts the interval, returning the key to save under.
+    pub(crate) fn saved(&mut self) -> String {
+        self.saved_at = js_sys::Date::now();
+        self.key.clone()
+    }
+}
+
+fn local_storage() -> Result<web_sys::Storage, JsError> {
+    web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .ok_or_else(|| JsError::new("localStorage is not available"))
+}
+pub(crate) fn write(key: &str, value: &str) -> Result<(), JsError> {
+    local_storage()?
+        .set_item(key, value)
+        .map_err(|_| JsError::new("localStorage is full"))
+}
+pub(crate) fn read(key: &str) -> Result<Option<String>, JsError> {
+    local_storage()?
+        .get_item(key)
+        .map_err(|_| JsError::new("localStorage can't be read"))
+}
+pub(crate) fn remove(key: &str) -> Result<(), JsError> {
+    local_storage()?
+        .remove_item(key)
+        .map_err(|_| JsError::new("localStorage can't be written"))
+}
@@ -0,0 +1,42 @@
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Boundary {
+    // the grid is a torus: leaving one edge re-enters on the opposite one
+    #[default]
+    Wrap = 0,
+    // everything outside the grid counts as permanently dead
+    Dead = 1,
+    // the outside is a reflection of the cells along the edge
+    Mirror = 2,
+}
+impl Boundary {
+    // Maps a possibly out-of-range coordinate onto the grid, or `None` when it
+    // falls off a dead edge.
+    pub(crate) fn resolve(self, coord: i64, size: u32) -> Option<u32> {
+        let size = size as i64;
+        if size == 0 {
+            return None;
+        }
+        if (0..size).contains(&coord) {
+            return Some(coord as u32);
+        }
+        match self {
+            Boundary::Wrap => Some(coord.rem_euclid(size) as u32),
+            Boundary::Dead => None,
+            Boundary::Mirror => {
+                let period = 2 * size;
+                let folded = coord.rem_euclid(period);
+                let reflected = if folded < size {
+                    folded
+                } else {
+                    period - folded - 1
+                };
+                Some(reflected as u32)
+            }
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use crate::rng::Rng;
+use crate::RING;
+use wasm_bindgen::prelude::*;
+
+// Procedural cave generator: a random fill of walls smoothed by the 4-5 rule,
+// where a wall stays a wall with at least `wall_survival` wall neighbors and
+// floor turns to wall with at least `wall_birth`. Everything beyond the edge
+// counts as wall, so caves come out closed. `cells()` is the map, 1 for wall
+// and 0 for floor, and `regions()` labels its connected floor areas.
+#[wasm_bindgen]
+pub struct Cave {
+    width: u32,
+    height: u32,
+    cells: Vec<u8>,
+    fill: f32,
+    wall_birth: u32,
+    wall_survival: u32,
+    // 4-connected floor region of every cell, 0 for walls and 1.. for regions
+    // in order of their first cell
+    regions: Vec<u32>,
+    region_sizes: Vec<u32>,
+    generation: u64,
+}
+#[wasm_bindgen]
+impl Cave {
+    fn get_index(&self, row: u32, column: u32) -> usize {
+        (row * self.width + column) as usize
+    }
+    fn wall_neighbors(&self, row: u32, column: u32) -> u32 {
+        RING.iter()
+            .filter(|&&(delta_row, delta_col)| {
+                let (row, col) = (row as i64 + delta_row, column as i64 + delta_col);
+                row < 0
+                    || col < 0
+                    || row >= self.height as i64
+                    || col >= self.width as i64
+                    || self.cells[self.get_index(row as u32, col as u32)] == 1
+            })
+            .count() as u32
+    }
+    fn label_regions(&mut self) {
+        self.regions = vec![0; self.cells.len()];
+        self.region_sizes.clear();
+        let mut stack = Vec::new();
+        for start in 0..self.cells.len() {
+            if self.cells[start] == 1 || self.regions[start] != 0 {
+                continue;
+            }
+            self.region_sizes.push(0);
+            let label = self.region_sizes.len() as u32;
+            self.regions[start] = label;
+            stack.push(start);
+            while let Some(idx) = stack.pop() {
+                self.region_sizes[label as usize - 1] += 1;
+                let (row, col) = (idx as u32 / self.width, idx as u32 % self.width);
+                let mut neighbors = Vec::with_capacity(4);
+                if row > 0 {
+                    neighbors.push(idx - self.width as usize);
+                }
+                if row + 1 < self.height {
+                    neighbors.push(idx + self.width as usize);
+                }
+                if col > 0 {
+                    neighbors.push(idx - 1);
+                }
+                if col + 1 < self.width {
+                    neighbors.push(idx + 1);
+                }
+                for neighbor in neighbors {
+                    if self.cells[neighbor] == 0 && self.regions[neighbor] == 0 {
+                        self.regions[neighbor] = label;
+                        stack.push(neighbor);
+                    }
+                }
+            }
+        }
+    }
+    pub fn new(width: u32, height: u32) -> Cave {
+        let mut cave = Cave {
+            width,
+            height,
+            cells: vec![0; (width * height) as usize],
+            fill: 0.45,
+            wall_birth: 5,
+            wall_survival: 4,
+            regions: Vec::new(),
+            region_sizes: Vec::new(),
+            generation: 0,
+        };
+        cave.generate(0, 5);
+        cave
+    }
+    // one smoothing pass
+    pub fn tick(&mut self) {
+        let mut next = self.cells.clone();
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let idx = self.get_index(row, col);
+                let walls = self.wall_neighbors(row, col);
+                let limit = if self.cells[idx] == 1 {
+                    self.wall_survival
+                } else {
+                    self.wall_birth
+                };
+                next[idx] = (walls >= limit) as u8;
+            }
+        }
+        self.cells = next;
+        self.label_regions();
+        self.generation += 1;
+    }
+    pub fn tick_n(&mut self, n: u32) {
+        for _ in 0..n {
+            self.tick();
+        }
+    }
+    // Random fill followed by `iterations` smoothing passes.
+    pub fn generate(&mut self, seed: u64, iterations: u32) {
+        let mut rng = Rng::new(seed);
+        for cell in self.cells.iter_mut() {
+            *cell = (rng.next_f32() < self.fill) as u8;
+        }
+        self.generation = 0;
+        self.label_regions();
+        self.tick_n(iterations);
+    }
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    // share of walls in the random fill
+    pub fn fill(&self) -> f32 {
+        self.fill
+    }
+    pub fn set_fill(&mut self, fill: f32) {
+        self.fill = fill.clamp(0.0, 1.0);
+    }
+    pub fn wall_birth(&self) -> u32 {
+        self.wall_birth
+    }
+    pub fn wall_survival(&self) -> u32 {
+        self.wall_survival
+    }
+    pub fn set_smoothing(&mut self, wall_birth: u32, wall_survival: u32) {
+        self.wall_birth = wall_birth;
+        self.wall_survival = wall_survival;
+    }
+    pub fn is_wall(&self, row: u32, column: u32) -> bool {
+        self.cells[self.get_index(row, column)] == 1
+    }
+    pub fn set_wall(&mut self, row: u32, column: u32, wall: bool) {
+        let idx = self.get_index(row, column);
+        self.cells[idx] = wall as u8;
+        self.label_regions();
+    }
+    pub fn region_count(&self) -> u32 {
+        self.region_sizes.len() as u32
+    }
+    // floor cells in each region, indexed by label - 1
+    pub fn region_sizes(&self) -> Vec<u32> {
+        self.region_sizes.clone()
+    }
+    // label of the biggest region, or 0 when there is no floor at all
+    pub fn largest_region(&self) -> u32 {
+        self.region_sizes
+            .iter()
+            .enumerate()
+            .max_by_key(|&(label, &size)| (size, std::cmp::Reverse(label)))
+            .map_or(0, |(label, _)| label as u32 + 1)
+    }
+    pub fn get_region(&self, row: u32, column: u32) -> u32 {
+        self.regions[self.get_index(row, column)]
+    }
+    // Walls in every floor region smaller than `min_size`, so only caves
+    // worth exploring are left.
+    pub fn fill_regions_smaller_than(&mut self, min_size: u32) {
+        for idx in 0..self.cells.len() {
+            let label = self.regions[idx];
+            if label != 0 && self.region_sizes[label as usize - 1] < min_size {
+                self.cells[idx] = 1;
+            }
+        }
+        self.label_regions();
+    }
+    pub fn regions(&self) -> js_sys::Uint32Array {
+        unsafe { js_sys::Uint32Array::view(&self.regions) }
+    }
+    pub fn cells(&self) -> js_sys::Uint8Array {
+        unsafe { js_sys::Uint8Array::view(&self.cells) }
+    }
+    // owned copy of the map that stays valid after the cave changes
+    pub fn export_map(&self) -> js_sys::Uint8Array {
+        js_sys::Uint8Array::from(&self.cells[..])
+    }
+}
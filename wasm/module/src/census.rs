@@ -0,0 +1,84 @@
+use crate::apgcode;
+use crate::components;
+use crate::{Boundary, Neighborhood, Rule};
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+// Common names of the objects a random soup most often leaves behind.
+const NAMES: &[(&str, &str)] = &[
+    ("xs4_33", "block"),
+    ("xs6_696", "beehive"),
+    ("xs7_2596", "loaf"),
+    ("xs5_253", "boat"),
+    ("xs4_252", "tub"),
+    ("xs6_356", "ship"),
+    ("xs8_6996", "pond"),
+    ("xp2_7", "blinker"),
+    ("xp2_7e", "toad"),
+    ("xp2_318c", "beacon"),
+    ("xq4_153", "glider"),
+    ("xq4_6frc", "lightweight spaceship"),
+    ("xq4_27dee6", "middleweight spaceship"),
+    ("xq4_27deee6", "heavyweight spaceship"),
+];
+
+// How many objects of one kind a census found.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct CensusEntry {
+    apgcode: Option<String>,
+    count: u32,
+}
+#[wasm_bindgen]
+impl CensusEntry {
+    // None for the objects that didn't repeat in time to be identified
+    pub fn apgcode(&self) -> Option<String> {
+        self.apgcode.clone()
+    }
+    // "block", "glider" and the like for common objects, "unidentified" for
+    // the entry without an apgcode, and None for the rest.
+    pub fn name(&self) -> Option<String> {
+        let name = match self.apgcode.as_deref() {
+            Some(code) => NAMES.iter().find(|(known, _)| *known == code)?.1,
+            None => "unidentified",
+        };
+        Some(name.to_string())
+    }
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+}
+
+// Splits the live cells of a `width` x `height` grid into objects, live
+// cells no more than two cells apart, and tallies them by apgcode, most
+// common first. Objects that straddle a wrapped edge are split in two.
+pub(crate) fn census(
+    rule: &Rule,
+    cells: &[u8],
+    width: u32,
+    height: u32,
+    max_period: u32,
+) -> Vec<CensusEntry> {
+    let (labels, count) =
+        components::label(cells, width, height, Boundary::Dead, Neighborhood::Moore, 2);
+    let mut objects = vec![Vec::new(); count as usize];
+    for (idx, &label) in labels.iter().enumerate().filter(|(_, &label)| label > 0) {
+        let (row, col) = (idx as u32 / width, idx as u32 % width);
+        objects[label as usize - 1].push((row as i64, col as i64));
+    }
+    let mut counts: HashMap<Option<String>, u32> = HashMap::new();
+    for object in objects {
+        let code = apgcode::identify(rule, &object, max_period).ok();
+        *counts.entry(code).or_default() += 1;
+    }
+    let mut entries: Vec<CensusEntry> = counts
+        .into_iter()
+        .map(|(apgcode, count)| CensusEntry { apgcode, count })
+        .collect();
+    entries.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.apgcode.cmp(&b.apgcode))
+    });
+    entries
+}
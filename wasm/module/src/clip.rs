@@ -0,0 +1,54 @@
+use crate::pattern::Pattern;
+use crate::Cell;
+use wasm_bindgen::prelude::*;
+
+// How pasted cells combine with the cells already under them. Only Copy
+// lets the dead cells of what is pasted clear what was there.
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum BlendMode {
+    // union: live pasted cells are added
+    #[default]
+    Or = 0,
+    // live pasted cells toggle the cells under them, as for collisions
+    Xor = 1,
+    // intersection: only cells live in both stay
+    And = 2,
+    // the pasted rectangle replaces what was there, dead cells and all
+    Copy = 3,
+}
+impl BlendMode {
+    pub(crate) fn blend(self, current: u8, pasted: u8) -> u8 {
+        const DEAD: u8 = Cell::Dead as u8;
+        match self {
+            BlendMode::Or if pasted == DEAD => current,
+            BlendMode::Or | BlendMode::Copy => pasted,
+            BlendMode::Xor if pasted == DEAD => current,
+            BlendMode::Xor if current == DEAD => pasted,
+            BlendMode::Xor => DEAD,
+            BlendMode::And if pasted == DEAD => DEAD,
+            BlendMode::And => current,
+        }
+    }
+}
+
+// A rectangle of cells copied out of a universe, dead cells included, ready
+// to be pasted back anywhere.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct Clip {
+    pub(crate) pattern: Pattern,
+}
+#[wasm_bindgen]
+impl Clip {
+    pub fn width(&self) -> u32 {
+        self.pattern.width
+    }
+    pub fn height(&self) -> u32 {
+        self.pattern.height
+    }
+    pub fn population(&self) -> u32 {
+        self.pattern.cells.len() as u32
+    }
+}
@@ -0,0 +1,42 @@
+use wasm_bindgen::prelude::*;
+
+// Colored Life variants: every live cell carries one of `colors()` colors and
+// a newborn takes the color most of its live neighbors have.
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColorVariant {
+    #[default]
+    Off = 0,
+    Immigration = 1,
+    QuadLife = 2,
+}
+impl ColorVariant {
+    pub fn colors(self) -> u8 {
+        match self {
+            ColorVariant::Off => 0,
+            ColorVariant::Immigration => 2,
+            ColorVariant::QuadLife => 4,
+        }
+    }
+}
+
+// Color 1..=colors for a cell born to `parents`. A tie for the most common
+// color goes to the rarest color instead, which is how QuadLife gives the
+// fourth color to a cell born to three differently colored parents; any tie
+// left over goes to the lowest color.
+pub(crate) fn inherited_color(parents: impl Iterator<Item = u8>, colors: u8) -> u8 {
+    let mut counts = [0u32; 256];
+    for color in parents {
+        counts[color as usize] += 1;
+    }
+    let counts = &counts[1..=colors as usize];
+    let most = counts.iter().copied().max().unwrap_or(0);
+    let pick = if counts.iter().filter(|&&count| count == most).count() == 1 {
+        most
+    } else {
+        counts.iter().copied().min().unwrap_or(0)
+    };
+    counts.iter().position(|&count| count == pick).unwrap_or(0) as u8 + 1
+}
@@ -0,0 +1,37 @@
+use crate::neighborhood::neighbor_indices;
+use crate::{Boundary, Cell, Neighborhood};
+
+// Labels every live cell with its cluster of live cells joined through
+// `connectivity` neighbors within `radius`, numbering clusters from 1 in scan order and
+// leaving 0 for every other cell. Returns the labels and the cluster count.
+pub(crate) fn label(
+    cells: &[u8],
+    width: u32,
+    height: u32,
+    boundary: Boundary,
+    connectivity: Neighborhood,
+    radius: u32,
+) -> (Vec<u32>, u32) {
+    let offsets = connectivity.offsets(radius);
+    let mut labels = vec![0; cells.len()];
+    let mut count = 0;
+    let mut pending = Vec::new();
+    for start in 0..cells.len() {
+        if cells[start] != Cell::Alive as u8 || labels[start] != 0 {
+            continue;
+        }
+        count += 1;
+        labels[start] = count;
+        pending.push(start);
+        while let Some(idx) = pending.pop() {
+            let (row, col) = (idx as u32 / width, idx as u32 % width);
+            for neighbor in neighbor_indices(&offsets, boundary, row, col, width, height) {
+                if cells[neighbor] == Cell::Alive as u8 && labels[neighbor] == 0 {
+                    labels[neighbor] = count;
+                    pending.push(neighbor);
+                }
+            }
+        }
+    }
+    (labels, count)
+}
@@ -0,0 +1,124 @@
+use crate::{Boundary, Cell};
+
+// Whether the build targets wasm SIMD, e.g. with
+// RUSTFLAGS="-C target-feature=+simd128", so counting takes 16 cells a step.
+pub(crate) const SIMD: bool = cfg!(all(target_arch = "wasm32", target_feature = "simd128"));
+
+// Live neighbor counts of every cell on the radius 1 Moore neighborhood,
+// worked out a whole row at a time. The buffers are kept between ticks so
+// counting doesn't allocate.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct MooreCounts {
+    // 1 for each live cell, with a one-cell border holding whatever the
+    // boundary puts just outside the grid
+    padded: Vec<u8>,
+    counts: Vec<u8>,
+}
+impl MooreCounts {
+    // The live cells as 1s, `width + 2` to a row, framed by the cells the
+    // boundary puts just outside the grid.
+    pub(crate) fn pad(
+        &mut self,
+        cells: &[u8],
+        width: u32,
+        height: u32,
+        boundary: Boundary,
+    ) -> &[u8] {
+        let (padded_width, padded_height) = (width as usize + 2, height as usize + 2);
+        self.padded.clear();
+        self.padded.resize(padded_width * padded_height, 0);
+        if width == 0 || height == 0 {
+            return &self.padded;
+        }
+        for padded_row in 0..padded_height {
+            let row = match boundary.resolve(padded_row as i64 - 1, height) {
+                Some(row) => row,
+                None => continue,
+            };
+            for padded_col in 0..padded_width {
+                if let Some(col) = boundary.resolve(padded_col as i64 - 1, width) {
+                    let state = cells[(row * width + col) as usize];
+                    self.padded[padded_row * padded_width + padded_col] =
+                        (state == Cell::Alive as u8) as u8;
+                }
+            }
+        }
+        &self.padded
+    }
+    pub(crate) fn count(
+        &mut self,
+        cells: &[u8],
+        width: u32,
+        height: u32,
+        boundary: Boundary,
+    ) -> &[u8] {
+        self.pad(cells, width, height, boundary);
+        self.counts.clear();
+        self.counts.resize(cells.len(), 0);
+        if width == 0 || height == 0 {
+            return &self.counts;
+        }
+        let padded_width = width as usize + 2;
+        let padded = &self.padded;
+        let padded_row = |row: usize| &padded[row * padded_width..(row + 1) * padded_width];
+        for (row, counts) in self.counts.chunks_mut(width as usize).enumerate() {
+            count_row(
+                padded_row(row),
+                padded_row(row + 1),
+                padded_row(row + 2),
+                counts,
+            );
+        }
+        &self.counts
+    }
+}
+
+// Counts for one row from the padded rows above, at and below it, from
+// column `from` on.
+fn count_cols(above: &[u8], row: &[u8], below: &[u8], counts: &mut [u8], from: usize) {
+    for col in from..counts.len() {
+        counts[col] = above[col]
+            + above[col + 1]
+            + above[col + 2]
+            + row[col]
+            + row[col + 2]
+            + below[col]
+            + below[col + 1]
+            + below[col + 2];
+    }
+}
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
+fn count_row(above: &[u8], row: &[u8], below: &[u8], counts: &mut [u8]) {
+    count_cols(above, row, below, counts, 0);
+}
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+fn count_row(above: &[u8], row: &[u8], below: &[u8], counts: &mut [u8]) {
+    use core::arch::wasm32::{u8x16_add, v128, v128_load, v128_store};
+    assert!(
+        above.len() == counts.len() + 2 && row.len() == above.len() && below.len() == above.len()
+    );
+    let mut col = 0;
+    while col + 16 <= counts.len() {
+        // SAFETY: the padded rows are two cells longer than `counts`, so every
+        // 16-byte load starting at col + 2 or before stays inside them, and
+        // the store at `col` stays inside `counts`.
+        unsafe {
+            let load = |cells: &[u8], offset: usize| {
+                v128_load(cells.as_ptr().add(col + offset) as *const v128)
+            };
+            let sum = u8x16_add(
+                u8x16_add(
+                    u8x16_add(load(above, 0), load(above, 1)),
+                    u8x16_add(load(above, 2), load(row, 0)),
+                ),
+                u8x16_add(
+                    u8x16_add(load(row, 2), load(below, 0)),
+                    u8x16_add(load(below, 1), load(below, 2)),
+                ),
+            );
+            v128_store(counts.as_mut_ptr().add(col) as *mut v128, sum);
+        }
+        col += 16;
+    }
+    count_cols(above, row, below, counts, col);
+}
@@ -0,0 +1,133 @@
+use crate::neighborhood::neighbor_indices;
+use crate::rng::Rng;
+use crate::{Boundary, Neighborhood};
+use wasm_bindgen::prelude::*;
+
+// Cyclic cellular automaton: each of the `states` colors is eaten by the next
+// one around the cycle, so a cell in state k moves on to k + 1 (mod states)
+// once at least `threshold` of its neighbors are already there.
+#[wasm_bindgen]
+pub struct Cyclic {
+    width: u32,
+    height: u32,
+    cells: Vec<u8>,
+    states: u8,
+    threshold: u32,
+    neighborhood: Neighborhood,
+    radius: u32,
+    neighbor_offsets: Vec<(i64, i64)>,
+    boundary: Boundary,
+    generation: u64,
+}
+#[wasm_bindgen]
+impl Cyclic {
+    fn get_index(&self, row: u32, column: u32) -> usize {
+        (row * self.width + column) as usize
+    }
+    // Random start with the classic 14-color, threshold 1, Moore settings
+    // that settle into spirals.
+    pub fn new(width: u32, height: u32) -> Cyclic {
+        let mut cyclic = Cyclic {
+            width,
+            height,
+            cells: vec![0; (width * height) as usize],
+            states: 14,
+            threshold: 1,
+            neighborhood: Neighborhood::Moore,
+            radius: 1,
+            neighbor_offsets: Neighborhood::Moore.offsets(1),
+            boundary: Boundary::default(),
+            generation: 0,
+        };
+        cyclic.randomize(0);
+        cyclic
+    }
+    pub fn tick(&mut self) {
+        let mut next = self.cells.clone();
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let idx = self.get_index(row, col);
+                let successor = (self.cells[idx] + 1) % self.states;
+                let eaters = neighbor_indices(
+                    &self.neighbor_offsets,
+                    self.boundary,
+                    row,
+                    col,
+                    self.width,
+                    self.height,
+                )
+                .filter(|&neighbor| self.cells[neighbor] == successor)
+                .count() as u32;
+                if eaters >= self.threshold {
+                    next[idx] = successor;
+                }
+            }
+        }
+        self.cells = next;
+        self.generation += 1;
+    }
+    pub fn tick_n(&mut self, n: u32) {
+        for _ in 0..n {
+            self.tick();
+        }
+    }
+    // Every cell gets a uniformly random state.
+    pub fn randomize(&mut self, seed: u64) {
+        let mut rng = Rng::new(seed);
+        for cell in self.cells.iter_mut() {
+            *cell = (rng.next_u64() % self.states as u64) as u8;
+        }
+        self.generation = 0;
+    }
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+    // Cells in states the new cycle doesn't have wrap around into it.
+    pub fn set_states(&mut self, states: u8) {
+        self.states = states.max(2);
+        let states = self.states;
+        self.cells.iter_mut().for_each(|cell| *cell %= states);
+    }
+    pub fn threshold(&self) -> u32 {
+        self.threshold
+    }
+    pub fn set_threshold(&mut self, threshold: u32) {
+        self.threshold = threshold.max(1);
+    }
+    pub fn neighborhood(&self) -> Neighborhood {
+        self.neighborhood
+    }
+    pub fn radius(&self) -> u32 {
+        self.radius
+    }
+    pub fn set_neighborhood(&mut self, neighborhood: Neighborhood, radius: u32) {
+        self.neighborhood = neighborhood;
+        self.radius = radius.max(1);
+        self.neighbor_offsets = neighborhood.offsets(self.radius);
+    }
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+    }
+    pub fn get_state(&self, row: u32, column: u32) -> u8 {
+        self.cells[self.get_index(row, column)]
+    }
+    pub fn set_state(&mut self, row: u32, column: u32, state: u8) {
+        let idx = self.get_index(row, column);
+        self.cells[idx] = state % self.states;
+    }
+    pub fn cells(&self) -> js_sys::Uint8Array {
+        unsafe { js_sys::Uint8Array::view(&self.cells) }
+    }
+}
@@ -0,0 +1,70 @@
+// Cells covered by simple shapes, as (row, column) pairs that may fall off
+// the grid.
+
+// Bresenham's line from (row0, col0) to (row1, col1), both ends included.
+pub(crate) fn line(row0: i64, col0: i64, row1: i64, col1: i64) -> Vec<(i64, i64)> {
+    let (delta_row, delta_col) = ((row1 - row0).abs(), -(col1 - col0).abs());
+    let (step_row, step_col) = ((row1 - row0).signum(), (col1 - col0).signum());
+    let (mut row, mut col) = (row0, col0);
+    let mut error = delta_row + delta_col;
+    let mut cells = Vec::new();
+    loop {
+        cells.push((row, col));
+        if (row, col) == (row1, col1) {
+            return cells;
+        }
+        let doubled = 2 * error;
+        if doubled >= delta_col {
+            error += delta_col;
+            row += step_row;
+        }
+        if doubled <= delta_row {
+            error += delta_row;
+            col += step_col;
+        }
+    }
+}
+pub(crate) fn rect(top: i64, left: i64, width: u32, height: u32, filled: bool) -> Vec<(i64, i64)> {
+    let (bottom, right) = (top + height as i64 - 1, left + width as i64 - 1);
+    let mut cells = Vec::new();
+    for row in top..=bottom {
+        for col in left..=right {
+            if filled || row == top || row == bottom || col == left || col == right {
+                cells.push((row, col));
+            }
+        }
+    }
+    cells
+}
+// The ellipse inscribed in the `width` x `height` box at (top, left): every
+// cell whose center lies inside it, or just those of them with a neighbor
+// outside for the outline.
+pub(crate) fn ellipse(
+    top: i64,
+    left: i64,
+    width: u32,
+    height: u32,
+    filled: bool,
+) -> Vec<(i64, i64)> {
+    let (radius_col, radius_row) = (width as f64 / 2.0, height as f64 / 2.0);
+    let inside = |row: i64, col: i64| {
+        if row < 0 || col < 0 || row >= height as i64 || col >= width as i64 {
+            return false;
+        }
+        let y = (row as f64 + 0.5 - radius_row) / radius_row;
+        let x = (col as f64 + 0.5 - radius_col) / radius_col;
+        x * x + y * y <= 1.0
+    };
+    let mut cells = Vec::new();
+    for row in 0..height as i64 {
+        for col in 0..width as i64 {
+            let edge = [(-1, 0), (1, 0), (0, -1), (0, 1)]
+                .iter()
+                .any(|&(dr, dc)| !inside(row + dr, col + dc));
+            if inside(row, col) && (filled || edge) {
+                cells.push((top + row, left + col));
+            }
+        }
+    }
+    cells
+}
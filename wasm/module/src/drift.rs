@@ -0,0 +1,55 @@
+use std::collections::VecDeque;
+
+// Centers of mass after each of the latest `window` ticks, oldest first, for
+// how fast the live cells are travelling.
+#[derive(Clone, Debug)]
+pub(crate) struct Drift {
+    window: usize,
+    centers: VecDeque<(f64, f64)>,
+}
+impl Default for Drift {
+    fn default() -> Self {
+        Drift {
+            window: 16,
+            centers: VecDeque::new(),
+        }
+    }
+}
+impl Drift {
+    pub(crate) fn window(&self) -> usize {
+        self.window
+    }
+    pub(crate) fn set_window(&mut self, window: usize) {
+        self.window = window.max(2);
+        while self.centers.len() > self.window {
+            self.centers.pop_front();
+        }
+    }
+    // An extinct generation has no center and breaks the run.
+    pub(crate) fn push(&mut self, center: Option<(f64, f64)>) {
+        let center = match center {
+            Some(center) => center,
+            None => return self.clear(),
+        };
+        if self.centers.len() == self.window {
+            self.centers.pop_front();
+        }
+        self.centers.push_back(center);
+    }
+    // (rows, columns) per tick between the oldest and newest center
+    pub(crate) fn velocity(&self) -> Option<(f64, f64)> {
+        let (&(first_row, first_col), &(last_row, last_col)) =
+            (self.centers.front()?, self.centers.back()?);
+        let ticks = (self.centers.len() - 1) as f64;
+        if ticks == 0.0 {
+            return None;
+        }
+        Some((
+            (last_row - first_row) / ticks,
+            (last_col - first_col) / ticks,
+        ))
+    }
+    pub(crate) fn clear(&mut self) {
+        self.centers.clear();
+    }
+}
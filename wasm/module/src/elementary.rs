@@ -0,0 +1,125 @@
+use crate::rng::Rng;
+use crate::{Boundary, Cell};
+use wasm_bindgen::prelude::*;
+
+// One-dimensional two-state automaton under a Wolfram rule (0-255), drawn as
+// a space-time diagram: every tick computes the next row below the newest
+// one, and once the grid is full the history scrolls up to make room.
+#[wasm_bindgen]
+pub struct Elementary {
+    width: u32,
+    height: u32,
+    cells: Vec<u8>,
+    rule: u8,
+    boundary: Boundary,
+    // row holding the newest generation
+    row: u32,
+    generation: u64,
+}
+#[wasm_bindgen]
+impl Elementary {
+    fn new_row_index(&self) -> usize {
+        (self.row * self.width) as usize
+    }
+    fn current(&self, column: i64) -> u8 {
+        match self.boundary.resolve(column, self.width) {
+            Some(column) => {
+                (self.cells[self.new_row_index() + column as usize] == Cell::Alive as u8) as u8
+            }
+            None => 0,
+        }
+    }
+    pub fn new(width: u32, height: u32, rule: u8) -> Elementary {
+        let mut elementary = Elementary {
+            width,
+            height,
+            cells: vec![Cell::Dead as u8; (width * height) as usize],
+            rule,
+            boundary: Boundary::default(),
+            row: 0,
+            generation: 0,
+        };
+        elementary.seed_center();
+        elementary
+    }
+    pub fn tick(&mut self) {
+        if self.height == 0 {
+            return;
+        }
+        let next: Vec<u8> = (0..self.width as i64)
+            .map(|col| {
+                let pattern =
+                    self.current(col - 1) << 2 | self.current(col) << 1 | self.current(col + 1);
+                (self.rule >> pattern) & 1
+            })
+            .collect();
+        if self.row + 1 < self.height {
+            self.row += 1;
+        } else {
+            self.cells.drain(..self.width as usize);
+            self.cells
+                .resize((self.width * self.height) as usize, Cell::Dead as u8);
+        }
+        let start = self.new_row_index();
+        self.cells[start..start + self.width as usize].copy_from_slice(&next);
+        self.generation += 1;
+    }
+    pub fn tick_n(&mut self, n: u32) {
+        for _ in 0..n {
+            self.tick();
+        }
+    }
+    // Restarts the diagram from a first row holding a single live cell.
+    pub fn seed_center(&mut self) {
+        self.clear();
+        if self.width > 0 && self.height > 0 {
+            self.cells[(self.width / 2) as usize] = Cell::Alive as u8;
+        }
+    }
+    // Restarts the diagram from a random first row.
+    pub fn seed_random(&mut self, density: f32, seed: u64) {
+        self.clear();
+        let mut rng = Rng::new(seed);
+        for cell in self.cells.iter_mut().take(self.width as usize) {
+            if rng.next_f32() < density {
+                *cell = Cell::Alive as u8;
+            }
+        }
+    }
+    pub fn clear(&mut self) {
+        self.cells.iter_mut().for_each(|c| *c = Cell::Dead as u8);
+        self.row = 0;
+        self.generation = 0;
+    }
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    pub fn rule(&self) -> u8 {
+        self.rule
+    }
+    pub fn set_rule(&mut self, rule: u8) {
+        self.rule = rule;
+    }
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+    }
+    pub fn get_cell(&self, row: u32, column: u32) -> Cell {
+        if self.cells[(row * self.width + column) as usize] == Cell::Alive as u8 {
+            Cell::Alive
+        } else {
+            Cell::Dead
+        }
+    }
+    pub fn cells(&self) -> js_sys::Uint8Array {
+        unsafe { js_sys::Uint8Array::view(&self.cells) }
+    }
+}
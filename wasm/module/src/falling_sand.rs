@@ -0,0 +1,163 @@
+use crate::rng::Rng;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Material {
+    Empty = 0,
+    Sand = 1,
+    Water = 2,
+    Wall = 3,
+}
+impl Material {
+    fn from_state(state: u8) -> Material {
+        match state {
+            1 => Material::Sand,
+            2 => Material::Water,
+            3 => Material::Wall,
+            _ => Material::Empty,
+        }
+    }
+    // whether a grain of `self` can move into a cell holding `other`
+    fn displaces(self, other: Material) -> bool {
+        match self {
+            Material::Sand => matches!(other, Material::Empty | Material::Water),
+            Material::Water => other == Material::Empty,
+            Material::Empty | Material::Wall => false,
+        }
+    }
+}
+// Falling-sand sandbox. Rows are updated from the bottom up so a grain falls
+// at most one cell per tick, and each row is scanned in alternating
+// directions so neither side is favored. The edges of the grid act as walls.
+#[wasm_bindgen]
+pub struct FallingSand {
+    width: u32,
+    height: u32,
+    cells: Vec<u8>,
+    // whether the cell was already written this tick
+    moved: Vec<bool>,
+    rng: Rng,
+    generation: u64,
+}
+#[wasm_bindgen]
+impl FallingSand {
+    fn get_index(&self, row: u32, column: u32) -> usize {
+        (row * self.width + column) as usize
+    }
+    fn material_at(&self, row: i64, column: i64) -> Material {
+        if row < 0 || column < 0 || row >= self.height as i64 || column >= self.width as i64 {
+            Material::Wall
+        } else {
+            Material::from_state(self.cells[self.get_index(row as u32, column as u32)])
+        }
+    }
+    fn try_move(&mut self, row: u32, column: u32, delta_row: i64, delta_col: i64) -> bool {
+        let material = Material::from_state(self.cells[self.get_index(row, column)]);
+        let (to_row, to_col) = (row as i64 + delta_row, column as i64 + delta_col);
+        if !material.displaces(self.material_at(to_row, to_col)) {
+            return false;
+        }
+        let from = self.get_index(row, column);
+        let to = self.get_index(to_row as u32, to_col as u32);
+        self.cells.swap(from, to);
+        self.moved[from] = true;
+        self.moved[to] = true;
+        true
+    }
+    pub fn new(width: u32, height: u32, seed: u64) -> FallingSand {
+        FallingSand {
+            width,
+            height,
+            cells: vec![Material::Empty as u8; (width * height) as usize],
+            moved: vec![false; (width * height) as usize],
+            rng: Rng::new(seed),
+            generation: 0,
+        }
+    }
+    pub fn tick(&mut self) {
+        self.moved.iter_mut().for_each(|moved| *moved = false);
+        let left_to_right = self.generation.is_multiple_of(2);
+        for row in (0..self.height).rev() {
+            for i in 0..self.width {
+                let col = if left_to_right { i } else { self.width - 1 - i };
+                let idx = self.get_index(row, col);
+                if self.moved[idx] {
+                    continue;
+                }
+                let side = if self.rng.next_u64() & 1 == 0 { -1 } else { 1 };
+                match Material::from_state(self.cells[idx]) {
+                    Material::Sand => {
+                        let _ = self.try_move(row, col, 1, 0)
+                            || self.try_move(row, col, 1, side)
+                            || self.try_move(row, col, 1, -side);
+                    }
+                    Material::Water => {
+                        let _ = self.try_move(row, col, 1, 0)
+                            || self.try_move(row, col, 1, side)
+                            || self.try_move(row, col, 1, -side)
+                            || self.try_move(row, col, 0, side)
+                            || self.try_move(row, col, 0, -side);
+                    }
+                    Material::Empty | Material::Wall => {}
+                }
+            }
+        }
+        self.generation += 1;
+    }
+    pub fn tick_n(&mut self, n: u32) {
+        for _ in 0..n {
+            self.tick();
+        }
+    }
+    // Fills a disc of `radius` cells around (row, column).
+    pub fn paint(&mut self, row: u32, column: u32, radius: u32, material: Material) {
+        self.spray(row, column, radius, material, 1.0);
+    }
+    // Like paint, but each cell of the disc is only filled with probability
+    // `density`.
+    pub fn spray(&mut self, row: u32, column: u32, radius: u32, material: Material, density: f32) {
+        let r = radius as i64;
+        for delta_row in -r..=r {
+            for delta_col in -r..=r {
+                if delta_row * delta_row + delta_col * delta_col > r * r {
+                    continue;
+                }
+                let (row, col) = (row as i64 + delta_row, column as i64 + delta_col);
+                if row < 0 || col < 0 || row >= self.height as i64 || col >= self.width as i64 {
+                    continue;
+                }
+                if density >= 1.0 || self.rng.next_f32() < density {
+                    let idx = self.get_index(row as u32, col as u32);
+                    self.cells[idx] = material as u8;
+                }
+            }
+        }
+    }
+    pub fn get_cell(&self, row: u32, column: u32) -> Material {
+        Material::from_state(self.cells[self.get_index(row, column)])
+    }
+    pub fn set_cell(&mut self, row: u32, column: u32, material: Material) {
+        let idx = self.get_index(row, column);
+        self.cells[idx] = material as u8;
+    }
+    pub fn clear(&mut self) {
+        self.cells
+            .iter_mut()
+            .for_each(|c| *c = Material::Empty as u8);
+        self.generation = 0;
+    }
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    pub fn cells(&self) -> js_sys::Uint8Array {
+        unsafe { js_sys::Uint8Array::view(&self.cells) }
+    }
+}
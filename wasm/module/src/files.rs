@@ -0,0 +1,30 @@
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum FileFormat {
+    #[default]
+    Rle = 0,
+    Macrocell = 1,
+    // the binary save() snapshot
+    Snapshot = 2,
+}
+impl FileFormat {
+    fn mime_type(self) -> &'static str {
+        match self {
+            FileFormat::Rle | FileFormat::Macrocell => "text/plain",
+            FileFormat::Snapshot => "application/octet-stream",
+        }
+    }
+}
+
+// Object URL of a Blob holding `contents`, to hand to a download link. The
+// caller revokes it with URL.revokeObjectURL() once done.
+pub(crate) fn object_url(contents: &[u8], format: FileFormat) -> Result<String, JsValue> {
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(contents));
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type(format.mime_type());
+    let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &options)?;
+    web_sys::Url::create_object_url_with_blob(&blob)
+}
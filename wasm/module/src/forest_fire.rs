@@ -0,0 +1,156 @@
+use crate::neighborhood::neighbor_indices;
+use crate::rng::Rng;
+use crate::{Boundary, Neighborhood};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ForestCell {
+    Empty = 0,
+    Tree = 1,
+    Burning = 2,
+}
+impl ForestCell {
+    fn from_state(state: u8) -> ForestCell {
+        match state {
+            1 => ForestCell::Tree,
+            2 => ForestCell::Burning,
+            _ => ForestCell::Empty,
+        }
+    }
+}
+// Drossel-Schwabl forest-fire model: burning trees burn out, trees next to a
+// fire catch it, and otherwise trees grow on empty ground with probability
+// `growth` and are struck by lightning with probability `lightning`.
+#[wasm_bindgen]
+pub struct ForestFire {
+    width: u32,
+    height: u32,
+    cells: Vec<u8>,
+    growth: f32,
+    lightning: f32,
+    neighbor_offsets: Vec<(i64, i64)>,
+    neighborhood: Neighborhood,
+    boundary: Boundary,
+    rng: Rng,
+    generation: u64,
+}
+#[wasm_bindgen]
+impl ForestFire {
+    fn get_index(&self, row: u32, column: u32) -> usize {
+        (row * self.width + column) as usize
+    }
+    fn near_fire(&self, row: u32, column: u32) -> bool {
+        neighbor_indices(
+            &self.neighbor_offsets,
+            self.boundary,
+            row,
+            column,
+            self.width,
+            self.height,
+        )
+        .any(|idx| self.cells[idx] == ForestCell::Burning as u8)
+    }
+    fn count(&self, cell: ForestCell) -> u32 {
+        self.cells.iter().filter(|&&c| c == cell as u8).count() as u32
+    }
+    pub fn new(width: u32, height: u32, seed: u64) -> ForestFire {
+        ForestFire {
+            width,
+            height,
+            cells: vec![ForestCell::Empty as u8; (width * height) as usize],
+            growth: 0.05,
+            lightning: 0.00005,
+            // the model is defined on nearest neighbors
+            neighbor_offsets: Neighborhood::VonNeumann.offsets(1),
+            neighborhood: Neighborhood::VonNeumann,
+            boundary: Boundary::default(),
+            rng: Rng::new(seed),
+            generation: 0,
+        }
+    }
+    pub fn tick(&mut self) {
+        let mut next = self.cells.clone();
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let idx = self.get_index(row, col);
+                let next_cell = match ForestCell::from_state(self.cells[idx]) {
+                    ForestCell::Burning => ForestCell::Empty,
+                    ForestCell::Tree if self.near_fire(row, col) => ForestCell::Burning,
+                    ForestCell::Tree if self.rng.next_f32() < self.lightning => ForestCell::Burning,
+                    ForestCell::Tree => ForestCell::Tree,
+                    ForestCell::Empty if self.rng.next_f32() < self.growth => ForestCell::Tree,
+                    ForestCell::Empty => ForestCell::Empty,
+                };
+                next[idx] = next_cell as u8;
+            }
+        }
+        self.cells = next;
+        self.generation += 1;
+    }
+    pub fn tick_n(&mut self, n: u32) {
+        for _ in 0..n {
+            self.tick();
+        }
+    }
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+    pub fn clear(&mut self) {
+        self.cells
+            .iter_mut()
+            .for_each(|c| *c = ForestCell::Empty as u8);
+        self.generation = 0;
+    }
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    pub fn growth(&self) -> f32 {
+        self.growth
+    }
+    pub fn set_growth(&mut self, growth: f32) {
+        self.growth = growth;
+    }
+    pub fn lightning(&self) -> f32 {
+        self.lightning
+    }
+    pub fn set_lightning(&mut self, lightning: f32) {
+        self.lightning = lightning;
+    }
+    pub fn neighborhood(&self) -> Neighborhood {
+        self.neighborhood
+    }
+    pub fn set_neighborhood(&mut self, neighborhood: Neighborhood) {
+        self.neighborhood = neighborhood;
+        self.neighbor_offsets = neighborhood.offsets(1);
+    }
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+    }
+    pub fn get_cell(&self, row: u32, column: u32) -> ForestCell {
+        ForestCell::from_state(self.cells[self.get_index(row, column)])
+    }
+    pub fn set_cell(&mut self, row: u32, column: u32, cell: ForestCell) {
+        let idx = self.get_index(row, column);
+        self.cells[idx] = cell as u8;
+    }
+    pub fn tree_count(&self) -> u32 {
+        self.count(ForestCell::Tree)
+    }
+    pub fn burning_count(&self) -> u32 {
+        self.count(ForestCell::Burning)
+    }
+    pub fn cells(&self) -> js_sys::Uint8Array {
+        unsafe { js_sys::Uint8Array::view(&self.cells) }
+    }
+}
@@ -0,0 +1,204 @@
+use crate::rng::Rng;
+use crate::Boundary;
+use wasm_bindgen::prelude::*;
+
+// 3x3 Laplacian weights: center, orthogonal, diagonal
+const LAPLACIAN: [(i64, i64, f32); 9] = [
+    (0, 0, -1.0),
+    (-1, 0, 0.2),
+    (1, 0, 0.2),
+    (0, -1, 0.2),
+    (0, 1, 0.2),
+    (-1, -1, 0.05),
+    (-1, 1, 0.05),
+    (1, -1, 0.05),
+    (1, 1, 0.05),
+];
+// concentration of V that maps to full intensity in `cells()`
+const V_SCALE: f32 = 0.5;
+
+// Gray-Scott reaction-diffusion: chemicals U and V diffuse at their own rates
+// while U + 2V -> 3V, U is fed in at `feed` and V removed at `kill`. Dead edges
+// read as the unreacted state, U = 1 and V = 0.
+#[wasm_bindgen]
+pub struct GrayScott {
+    width: u32,
+    height: u32,
+    u: Vec<f32>,
+    v: Vec<f32>,
+    // V mapped to 0-255, refreshed every tick
+    cells: Vec<u8>,
+    diffusion_u: f32,
+    diffusion_v: f32,
+    feed: f32,
+    kill: f32,
+    dt: f32,
+    boundary: Boundary,
+    generation: u64,
+}
+#[wasm_bindgen]
+impl GrayScott {
+    fn get_index(&self, row: u32, column: u32) -> usize {
+        (row * self.width + column) as usize
+    }
+    fn laplacians(&self, row: u32, column: u32) -> (f32, f32) {
+        let mut lu = 0.0;
+        let mut lv = 0.0;
+        for &(delta_row, delta_col, weight) in LAPLACIAN.iter() {
+            let (u, v) = match (
+                self.boundary.resolve(row as i64 + delta_row, self.height),
+                self.boundary.resolve(column as i64 + delta_col, self.width),
+            ) {
+                (Some(row), Some(col)) => {
+                    let idx = self.get_index(row, col);
+                    (self.u[idx], self.v[idx])
+                }
+                _ => (1.0, 0.0),
+            };
+            lu += weight * u;
+            lv += weight * v;
+        }
+        (lu, lv)
+    }
+    fn refresh_cells(&mut self) {
+        for (cell, &v) in self.cells.iter_mut().zip(self.v.iter()) {
+            *cell = ((v / V_SCALE).clamp(0.0, 1.0) * 255.0) as u8;
+        }
+    }
+    // The "coral" parameters, starting from a single seeded square.
+    pub fn new(width: u32, height: u32) -> GrayScott {
+        let size = (width * height) as usize;
+        let mut gray_scott = GrayScott {
+            width,
+            height,
+            u: vec![1.0; size],
+            v: vec![0.0; size],
+            cells: vec![0; size],
+            diffusion_u: 1.0,
+            diffusion_v: 0.5,
+            feed: 0.0545,
+            kill: 0.062,
+            dt: 1.0,
+            boundary: Boundary::default(),
+            generation: 0,
+        };
+        gray_scott.seed(height / 2, width / 2, (width.min(height) / 10).max(1));
+        gray_scott
+    }
+    pub fn tick(&mut self) {
+        let mut next_u = self.u.clone();
+        let mut next_v = self.v.clone();
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let idx = self.get_index(row, col);
+                let (u, v) = (self.u[idx], self.v[idx]);
+                let (lu, lv) = self.laplacians(row, col);
+                let reaction = u * v * v;
+                next_u[idx] =
+                    u + self.dt * (self.diffusion_u * lu - reaction + self.feed * (1.0 - u));
+                next_v[idx] =
+                    v + self.dt * (self.diffusion_v * lv + reaction - (self.kill + self.feed) * v);
+            }
+        }
+        self.u = next_u;
+        self.v = next_v;
+        self.refresh_cells();
+        self.generation += 1;
+    }
+    pub fn tick_n(&mut self, n: u32) {
+        for _ in 0..n {
+            self.tick();
+        }
+    }
+    // Drops a square of V with the given half-size centered on (row, column).
+    pub fn seed(&mut self, row: u32, column: u32, radius: u32) {
+        let r = radius as i64;
+        for delta_row in -r..=r {
+            for delta_col in -r..=r {
+                if let (Some(row), Some(col)) = (
+                    self.boundary.resolve(row as i64 + delta_row, self.height),
+                    self.boundary.resolve(column as i64 + delta_col, self.width),
+                ) {
+                    let idx = self.get_index(row, col);
+                    self.u[idx] = 0.5;
+                    self.v[idx] = 0.25;
+                }
+            }
+        }
+        self.refresh_cells();
+    }
+    // Resets both fields and seeds `spots` squares at random positions.
+    pub fn randomize(&mut self, spots: u32, seed: u64) {
+        self.u.iter_mut().for_each(|u| *u = 1.0);
+        self.v.iter_mut().for_each(|v| *v = 0.0);
+        let mut rng = Rng::new(seed);
+        if self.width > 0 && self.height > 0 {
+            let radius = (self.width.min(self.height) / 40).max(1);
+            for _ in 0..spots {
+                let row = (rng.next_u64() % self.height as u64) as u32;
+                let col = (rng.next_u64() % self.width as u64) as u32;
+                self.seed(row, col, radius);
+            }
+        }
+        self.refresh_cells();
+        self.generation = 0;
+    }
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    pub fn feed(&self) -> f32 {
+        self.feed
+    }
+    pub fn set_feed(&mut self, feed: f32) {
+        self.feed = feed;
+    }
+    pub fn kill(&self) -> f32 {
+        self.kill
+    }
+    pub fn set_kill(&mut self, kill: f32) {
+        self.kill = kill;
+    }
+    pub fn diffusion_u(&self) -> f32 {
+        self.diffusion_u
+    }
+    pub fn diffusion_v(&self) -> f32 {
+        self.diffusion_v
+    }
+    pub fn set_diffusion(&mut self, diffusion_u: f32, diffusion_v: f32) {
+        self.diffusion_u = diffusion_u;
+        self.diffusion_v = diffusion_v;
+    }
+    pub fn dt(&self) -> f32 {
+        self.dt
+    }
+    pub fn set_dt(&mut self, dt: f32) {
+        self.dt = dt;
+    }
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+    }
+    pub fn get_u(&self, row: u32, column: u32) -> f32 {
+        self.u[self.get_index(row, column)]
+    }
+    pub fn get_v(&self, row: u32, column: u32) -> f32 {
+        self.v[self.get_index(row, column)]
+    }
+    pub fn u_field(&self) -> js_sys::Float32Array {
+        unsafe { js_sys::Float32Array::view(&self.u) }
+    }
+    pub fn v_field(&self) -> js_sys::Float32Array {
+        unsafe { js_sys::Float32Array::view(&self.v) }
+    }
+    pub fn cells(&self) -> js_sys::Uint8Array {
+        unsafe { js_sys::Uint8Array::view(&self.cells) }
+    }
+}
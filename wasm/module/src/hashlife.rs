@@ -0,0 +1,418 @@
+use crate::apgcode;
+use crate::pattern::{self, Transform};
+use crate::plane::{check_unbounded_rule, next_alive};
+use crate::snapshot::MAX_CELLS;
+use crate::{Cell, Rule, RING};
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+// Largest super_step(), keeping every coordinate the quadtree reaches well
+// inside an i64.
+const MAX_LOG2_STEPS: u32 = 56;
+// Cells set farther than this from the origin either way are refused, as
+// the root grown to reach them would outgrow an i64.
+const MAX_COORDINATE: u64 = 1 << 56;
+// the leaves, single cells
+const DEAD: u32 = 0;
+const ALIVE: u32 = 1;
+
+// A 2^level x 2^level square: a single cell at level 0, and four squares of
+// the level below it otherwise, nw, ne, sw, se.
+#[derive(Clone, Copy, Debug)]
+struct Node {
+    children: [u32; 4],
+    level: u8,
+    population: u64,
+}
+
+// Gosper's HashLife: the plane as a quadtree in which equal squares are one
+// shared node, and each node remembers its center after every power of two
+// generations asked of it. Repetitive patterns such as breeders can then be
+// advanced billions of generations at once. It runs the same rules as
+// SparseUniverse; coordinates are f64 as patterns soon travel beyond i32.
+#[wasm_bindgen]
+pub struct HashLife {
+    rule: Rule,
+    nodes: Vec<Node>,
+    // node of every four children, so equal squares are built only once
+    joined: HashMap<[u32; 4], u32>,
+    // empty node of each level
+    empty: Vec<u32>,
+    // advance() of a node and log2 of the generations it went forward
+    advanced: HashMap<(u32, u32), u32>,
+    root: u32,
+    // (row, column) of the root's top-left corner
+    origin: (i64, i64),
+    generation: u64,
+}
+impl HashLife {
+    fn level(&self, node: u32) -> u8 {
+        self.nodes[node as usize].level
+    }
+    fn population_of(&self, node: u32) -> u64 {
+        self.nodes[node as usize].population
+    }
+    fn children(&self, node: u32) -> [u32; 4] {
+        self.nodes[node as usize].children
+    }
+    fn join(&mut self, children: [u32; 4]) -> u32 {
+        if let Some(&node) = self.joined.get(&children) {
+            return node;
+        }
+        let population = children.iter().fold(0u64, |sum, &child| {
+            sum.saturating_add(self.population_of(child))
+        });
+        let node = self.nodes.len() as u32;
+        self.nodes.push(Node {
+            children,
+            level: self.level(children[0]) + 1,
+            population,
+        });
+        self.joined.insert(children, node);
+        node
+    }
+    fn empty(&mut self, level: u8) -> u32 {
+        while self.empty.len() <= level as usize {
+            let below = *self.empty.last().unwrap_or(&DEAD);
+            let node = if self.empty.is_empty() {
+                DEAD
+            } else {
+                self.join([below; 4])
+            };
+            self.empty.push(node);
+        }
+        self.empty[level as usize]
+    }
+    // the square of the level below centered on `node`
+    fn center(&mut self, node: u32) -> u32 {
+        let [nw, ne, sw, se] = self.children(node);
+        self.join([
+            self.children(nw)[3],
+            self.children(ne)[2],
+            self.children(sw)[1],
+            self.children(se)[0],
+        ])
+    }
+    // Puts the root in the middle of an empty square twice its size.
+    fn expand(&mut self) {
+        let level = self.level(self.root);
+        let empty = self.empty(level - 1);
+        let [nw, ne, sw, se] = self.children(self.root);
+        let children = [
+            self.join([empty, empty, empty, nw]),
+            self.join([empty, empty, ne, empty]),
+            self.join([empty, sw, empty, empty]),
+            self.join([se, empty, empty, empty]),
+        ];
+        self.root = self.join(children);
+        let half = 1i64 << (level - 1);
+        self.origin = (self.origin.0 - half, self.origin.1 - half);
+    }
+    // Whether the live cells all lie in the middle half of the root.
+    fn centered(&mut self) -> bool {
+        let center = self.center(self.root);
+        self.population_of(center) == self.population_of(self.root)
+    }
+    fn contains(&self, row: i64, col: i64) -> bool {
+        let size = 1i64 << self.level(self.root);
+        let (top, left) = self.origin;
+        (top..top + size).contains(&row) && (left..left + size).contains(&col)
+    }
+    // the 4 x 4 square `node` one generation on, as its middle 2 x 2
+    fn step_leaf(&mut self, node: u32) -> u32 {
+        let mut alive = [[false; 4]; 4];
+        for (quadrant, &child) in self.children(node).iter().enumerate() {
+            for (cell, &leaf) in self.children(child).iter().enumerate() {
+                let row = (quadrant / 2) * 2 + cell / 2;
+                let col = (quadrant % 2) * 2 + cell % 2;
+                alive[row][col] = leaf == ALIVE;
+            }
+        }
+        let mut next = [DEAD; 4];
+        for (cell, next) in next.iter_mut().enumerate() {
+            let (row, col) = (1 + cell / 2, 1 + cell % 2);
+            let mut config = 0u8;
+            for (bit, &(delta_row, delta_col)) in RING.iter().enumerate() {
+                let neighbor =
+                    alive[(row as i64 + delta_row) as usize][(col as i64 + delta_col) as usize];
+                config |= (neighbor as u8) << bit;
+            }
+            if next_alive(&self.rule, alive[row][col], config) {
+                *next = ALIVE;
+            }
+        }
+        self.join(next)
+    }
+    // The center of `node` 2^log2_steps generations on, where log2_steps is
+    // at most the node's level minus 2.
+    fn advance(&mut self, node: u32, log2_steps: u32) -> u32 {
+        let level = self.level(node);
+        if self.population_of(node) == 0 {
+            return self.empty(level - 1);
+        }
+        if let Some(&result) = self.advanced.get(&(node, log2_steps)) {
+            return result;
+        }
+        let result = if level == 2 {
+            self.step_leaf(node)
+        } else {
+            let [nw, ne, sw, se] = self.children(node);
+            let [nw_c, ne_c, sw_c, se_c] = [nw, ne, sw, se].map(|child| self.children(child));
+            let sub = [
+                nw,
+                self.join([nw_c[1], ne_c[0], nw_c[3], ne_c[2]]),
+                ne,
+                self.join([nw_c[2], nw_c[3], sw_c[0], sw_c[1]]),
+                self.join([nw_c[3], ne_c[2], sw_c[1], se_c[0]]),
+                self.join([ne_c[2], ne_c[3], se_c[0], se_c[1]]),
+                sw,
+                self.join([sw_c[1], se_c[0], sw_c[3], se_c[2]]),
+                se,
+            ];
+            // at full speed both halves of the trip go forward in time,
+            // otherwise only the second does
+            let full = log2_steps + 2 == level as u32;
+            let mut first = [DEAD; 9];
+            for (first, &sub) in first.iter_mut().zip(sub.iter()) {
+                *first = if full {
+                    self.advance(sub, log2_steps - 1)
+                } else {
+                    self.center(sub)
+                };
+            }
+            let second_steps = if full { log2_steps - 1 } else { log2_steps };
+            let mut quadrants = [DEAD; 4];
+            for (quadrant, result) in quadrants.iter_mut().enumerate() {
+                let (row, col) = (quadrant / 2, quadrant % 2);
+                let at = |r: usize, c: usize| first[(row + r) * 3 + col + c];
+                let square = self.join([at(0, 0), at(0, 1), at(1, 0), at(1, 1)]);
+                *result = self.advance(square, second_steps);
+            }
+            self.join(quadrants)
+        };
+        self.advanced.insert((node, log2_steps), result);
+        result
+    }
+    fn set(&mut self, node: u32, row: i64, col: i64, alive: bool) -> u32 {
+        let level = self.level(node);
+        if level == 0 {
+            return if alive { ALIVE } else { DEAD };
+        }
+        let half = 1i64 << (level - 1);
+        let quadrant = (row >= half) as usize * 2 + (col >= half) as usize;
+        let mut children = self.children(node);
+        children[quadrant] = self.set(children[quadrant], row % half, col % half, alive);
+        self.join(children)
+    }
+    // Fails for a cell past MAX_COORDINATE.
+    fn reachable(row: i64, col: i64) -> Result<(), JsError> {
+        if row.unsigned_abs() < MAX_COORDINATE && col.unsigned_abs() < MAX_COORDINATE {
+            Ok(())
+        } else {
+            Err(JsError::new("cell is too far from the origin"))
+        }
+    }
+    // Only for reachable() cells.
+    fn set_alive(&mut self, row: i64, col: i64, alive: bool) {
+        while !self.contains(row, col) {
+            self.expand();
+        }
+        let (top, left) = self.origin;
+        self.root = self.set(self.root, row - top, col - left, alive);
+    }
+    // Calls `visit` with every live cell of `node`, whose corner is at (top,
+    // left), inside the rows and columns given.
+    fn visit(
+        &self,
+        node: u32,
+        (top, left): (i64, i64),
+        rows: &std::ops::Range<i64>,
+        cols: &std::ops::Range<i64>,
+        visit: &mut impl FnMut(i64, i64),
+    ) {
+        let size = 1i64 << self.level(node);
+        if self.population_of(node) == 0
+            || top >= rows.end
+            || top + size <= rows.start
+            || left >= cols.end
+            || left + size <= cols.start
+        {
+            return;
+        }
+        if size == 1 {
+            return visit(top, left);
+        }
+        let half = size / 2;
+        for (quadrant, &child) in self.children(node).iter().enumerate() {
+            let corner = (
+                top + (quadrant / 2) as i64 * half,
+                left + (quadrant % 2) as i64 * half,
+            );
+            self.visit(child, corner, rows, cols, visit);
+        }
+    }
+}
+#[wasm_bindgen]
+impl HashLife {
+    pub fn new(rule: &str) -> Result<HashLife, JsError> {
+        let rule: Rule = rule.parse()?;
+        check_unbounded_rule(&rule)?;
+        let leaf = |population| Node {
+            children: [DEAD; 4],
+            level: 0,
+            population,
+        };
+        let mut hashlife = HashLife {
+            rule,
+            nodes: vec![leaf(0), leaf(1)],
+            joined: HashMap::new(),
+            empty: Vec::new(),
+            advanced: HashMap::new(),
+            root: DEAD,
+            origin: (-4, -4),
+            generation: 0,
+        };
+        hashlife.root = hashlife.empty(3);
+        Ok(hashlife)
+    }
+    // Advances 2^log2_steps generations at once, log2_steps up to 56.
+    pub fn super_step(&mut self, log2_steps: u32) {
+        let log2_steps = log2_steps.min(MAX_LOG2_STEPS);
+        // the pattern can spread 2^log2_steps cells every way, so it has to
+        // sit in the middle quarter of a root at least 4 times that wide
+        while (self.level(self.root) as u32) < log2_steps + 2 || !self.centered() {
+            self.expand();
+        }
+        self.expand();
+        let level = self.level(self.root);
+        self.root = self.advance(self.root, log2_steps);
+        let offset = 1i64 << (level - 2);
+        self.origin = (self.origin.0 + offset, self.origin.1 + offset);
+        while self.level(self.root) > 3 && self.centered() {
+            let offset = 1i64 << (self.level(self.root) - 2);
+            self.root = self.center(self.root);
+            self.origin = (self.origin.0 + offset, self.origin.1 + offset);
+        }
+        self.generation += 1 << log2_steps;
+    }
+    pub fn tick(&mut self) {
+        self.super_step(0);
+    }
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    pub fn rule(&self) -> String {
+        self.rule.to_string()
+    }
+    // saturates at u64::MAX
+    pub fn population(&self) -> u64 {
+        self.population_of(self.root)
+    }
+    // Distinct squares and remembered results held in memory, for deciding
+    // when to clear_cache().
+    pub fn node_count(&self) -> u32 {
+        self.nodes.len() as u32
+    }
+    // Forgets every remembered result and unused square, keeping the
+    // pattern. Later steps are slower until the memory is rebuilt.
+    pub fn clear_cache(&mut self) {
+        let mut cells = Vec::new();
+        let everything = i64::MIN..i64::MAX;
+        self.visit(
+            self.root,
+            self.origin,
+            &everything,
+            &everything,
+            &mut |row, col| cells.push((row, col)),
+        );
+        self.nodes.truncate(2);
+        self.joined.clear();
+        self.empty.clear();
+        self.advanced.clear();
+        self.root = self.empty(3);
+        self.origin = (-4, -4);
+        for (row, col) in cells {
+            self.set_alive(row, col, true);
+        }
+    }
+    pub fn get_cell(&self, row: f64, column: f64) -> Cell {
+        let (row, col) = (row as i64, column as i64);
+        let mut alive = false;
+        self.visit(
+            self.root,
+            self.origin,
+            &(row..row + 1),
+            &(col..col + 1),
+            &mut |_, _| alive = true,
+        );
+        if alive {
+            Cell::Alive
+        } else {
+            Cell::Dead
+        }
+    }
+    pub fn set_cell(&mut self, row: f64, column: f64, cell: Cell) -> Result<(), JsError> {
+        // NaN and the infinities turn into 0 and i64::MIN or MAX
+        let (row, col) = (row as i64, column as i64);
+        HashLife::reachable(row, col)?;
+        self.set_alive(row, col, cell == Cell::Alive);
+        Ok(())
+    }
+    pub fn clear(&mut self) {
+        self.root = self.empty(3);
+        self.origin = (-4, -4);
+        self.generation = 0;
+    }
+    // [min_row, min_column, max_row, max_column] of the live cells, or None
+    // when there are none.
+    pub fn bounding_box(&self) -> Option<Vec<f64>> {
+        let mut bounds: Option<(i64, i64, i64, i64)> = None;
+        let everything = i64::MIN..i64::MAX;
+        self.visit(
+            self.root,
+            self.origin,
+            &everything,
+            &everything,
+            &mut |row, col| {
+                let (top, left, bottom, right) = bounds.unwrap_or((row, col, row, col));
+                bounds = Some((top.min(row), left.min(col), bottom.max(row), right.max(col)));
+            },
+        );
+        let (top, left, bottom, right) = bounds?;
+        Some(vec![top as f64, left as f64, bottom as f64, right as f64])
+    }
+    // The `width` x `height` window with its top-left corner at (row,
+    // column), as one Cell per byte in row-major order.
+    pub fn window(&self, row: f64, column: f64, width: u32, height: u32) -> Vec<u8> {
+        let (top, left) = (row as i64, column as i64);
+        let mut cells = vec![Cell::Dead as u8; (width * height) as usize];
+        let (rows, cols) = (top..top + height as i64, left..left + width as i64);
+        self.visit(self.root, self.origin, &rows, &cols, &mut |row, col| {
+            cells[((row - top) * width as i64 + col - left) as usize] = Cell::Alive as u8;
+        });
+        cells
+    }
+    // Sets the live cells of a transformed RLE pattern with its top-left
+    // corner at (row, column).
+    pub fn paste_rle(
+        &mut self,
+        rle: &str,
+        row: f64,
+        column: f64,
+        transform: Transform,
+    ) -> Result<(), JsError> {
+        let pattern = pattern::parse_rle(rle, MAX_CELLS as usize)?.transformed(transform);
+        let (top, left) = (row as i64, column as i64);
+        let cells: Vec<(i64, i64)> = apgcode::live_cells(&pattern)
+            .into_iter()
+            .map(|(row, col)| (top.saturating_add(row), left.saturating_add(col)))
+            .collect();
+        for &(row, col) in cells.iter() {
+            HashLife::reachable(row, col)?;
+        }
+        for (row, col) in cells {
+            self.set_alive(row, col, true);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+// Undo and redo for edits, kept as the cells each edit changed rather than
+// copies of the grid.
+
+// oldest edits beyond this many are forgotten
+const LIMIT: usize = 256;
+
+#[derive(Clone, Copy, Debug)]
+struct Change {
+    index: usize,
+    before: u8,
+    after: u8,
+}
+#[derive(Clone, Debug, Default)]
+pub(crate) struct History {
+    undo: Vec<Vec<Change>>,
+    redo: Vec<Vec<Change>>,
+    // what the open edit changed so far, in the order it did
+    open: Vec<Change>,
+    // the cells as they were when the open edit began, taken instead once it
+    // rewrites the whole grid
+    base: Option<Vec<u8>>,
+    // set when the history is cleared in the middle of an edit, which then
+    // leaves no step
+    dropped: bool,
+    depth: u32,
+}
+impl History {
+    fn push(&mut self, changes: Vec<Change>) {
+        if changes.is_empty() {
+            return;
+        }
+        if self.undo.len() == LIMIT {
+            self.undo.remove(0);
+        }
+        self.undo.push(changes);
+        self.redo.clear();
+    }
+    // Edits nest: everything up to the matching end() is one undo step.
+    pub(crate) fn begin(&mut self) {
+        self.depth += 1;
+    }
+    pub(crate) fn end(&mut self, cells: &[u8]) {
+        if self.depth == 0 {
+            return;
+        }
+        self.depth -= 1;
+        if self.depth > 0 {
+            return;
+        }
+        let mut open = std::mem::take(&mut self.open);
+        let base = self.base.take();
+        if std::mem::take(&mut self.dropped) {
+            return;
+        }
+        let changes = match base {
+            Some(base) => base
+                .iter()
+                .zip(cells)
+                .enumerate()
+                .filter(|(_, (before, after))| before != after)
+                .map(|(index, (&before, &after))| Change {
+                    index,
+                    before,
+                    after,
+                })
+                .collect(),
+            None => {
+                // a cell edited more than once keeps the state it had first
+                open.sort_by_key(|change| change.index);
+                open.dedup_by_key(|change| change.index);
+                open.retain_mut(|change| {
+                    change.after = cells[change.index];
+                    change.before != change.after
+                });
+                open
+            }
+        };
+        self.push(changes);
+    }
+    // A single cell edited: its own step outside of any open edit, and part
+    // of the open one otherwise.
+    pub(crate) fn record(&mut self, index: usize, before: u8, after: u8) {
+        if before == after {
+            return;
+        }
+        let change = Change {
+            index,
+            before,
+            after,
+        };
+        if self.depth == 0 {
+            self.push(vec![change]);
+        } else if !self.dropped && self.base.is_none() {
+            self.open.push(change);
+        }
+    }
+    // For an open edit about to rewrite the whole grid, where keeping a copy
+    // of `cells` costs no more than the edit and less than recording every
+    // cell.
+    pub(crate) fn rewrite(&mut self, cells: &[u8]) {
+        if self.depth == 0 || self.dropped || self.base.is_some() {
+            return;
+        }
+        let mut base = cells.to_vec();
+        for change in self.open.drain(..).rev() {
+            base[change.index] = change.before;
+        }
+        self.base = Some(base);
+    }
+    pub(crate) fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        if self.depth > 0 {
+            self.open.clear();
+            self.base = None;
+            self.dropped = true;
+        }
+    }
+    pub(crate) fn is_open(&self) -> bool {
+        self.depth > 0
+    }
+    pub(crate) fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+    pub(crate) fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+    // (index, state) of every cell the last edit changed, as it was before.
+    pub(crate) fn undo(&mut self) -> Option<Vec<(usize, u8)>> {
+        let changes = self.undo.pop()?;
+        let states = changes.iter().map(|c| (c.index, c.before)).collect();
+        self.redo.push(changes);
+        Some(states)
+    }
+    pub(crate) fn redo(&mut self) -> Option<Vec<(usize, u8)>> {
+        let changes = self.redo.pop()?;
+        let states = changes.iter().map(|c| (c.index, c.after)).collect();
+        self.undo.push(changes);
+        Some(states)
+    }
+}
@@ -0,0 +1,222 @@
+use crate::rng::Rng;
+use crate::Boundary;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Spin {
+    Down = 0,
+    Up = 1,
+}
+impl Spin {
+    fn from_state(state: u8) -> Spin {
+        match state {
+            0 => Spin::Down,
+            _ => Spin::Up,
+        }
+    }
+}
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum IsingUpdate {
+    // one sweep is width * height flip attempts at random sites
+    #[default]
+    Metropolis = 0,
+    // one sweep visits every even site and then every odd site in order, so
+    // no two neighbors are updated against each other's stale spin
+    Checkerboard = 1,
+}
+
+// 2D Ising model with unit coupling in units where Boltzmann's constant is 1,
+// so the critical temperature is 2 / ln(1 + sqrt 2), about 2.269. A Dead
+// boundary gives free edges; on a Mirror edge a spin borders itself, which
+// only shifts the energy by a constant.
+#[wasm_bindgen]
+pub struct Ising {
+    width: u32,
+    height: u32,
+    cells: Vec<u8>,
+    temperature: f64,
+    field: f64,
+    update: IsingUpdate,
+    boundary: Boundary,
+    rng: Rng,
+    generation: u64,
+}
+#[wasm_bindgen]
+impl Ising {
+    fn get_index(&self, row: u32, column: u32) -> usize {
+        (row * self.width + column) as usize
+    }
+    fn spin(&self, idx: usize) -> i64 {
+        if self.cells[idx] == Spin::Up as u8 {
+            1
+        } else {
+            -1
+        }
+    }
+    fn neighbor(&self, row: u32, column: u32, delta_row: i64, delta_col: i64) -> Option<usize> {
+        let row = self.boundary.resolve(row as i64 + delta_row, self.height)?;
+        let col = self
+            .boundary
+            .resolve(column as i64 + delta_col, self.width)?;
+        Some(self.get_index(row, col))
+    }
+    fn neighbor_sum(&self, row: u32, column: u32) -> i64 {
+        let idx = self.get_index(row, column);
+        IntoIterator::into_iter([(-1, 0), (1, 0), (0, -1), (0, 1)])
+            .filter_map(|(delta_row, delta_col)| self.neighbor(row, column, delta_row, delta_col))
+            .filter(|&neighbor| neighbor != idx)
+            .map(|neighbor| self.spin(neighbor))
+            .sum()
+    }
+    // Metropolis step: flip when it lowers the energy, otherwise with
+    // probability exp(-dE / T).
+    fn attempt_flip(&mut self, row: u32, column: u32) {
+        let idx = self.get_index(row, column);
+        let spin = self.spin(idx) as f64;
+        let delta = 2.0 * spin * (self.neighbor_sum(row, column) as f64 + self.field);
+        let accept = delta <= 0.0
+            || (self.temperature > 0.0
+                && (self.rng.next_f32() as f64) < (-delta / self.temperature).exp());
+        if accept {
+            self.cells[idx] ^= 1;
+        }
+    }
+    pub fn new(width: u32, height: u32, seed: u64) -> Ising {
+        let mut ising = Ising {
+            width,
+            height,
+            cells: vec![Spin::Up as u8; (width * height) as usize],
+            temperature: 2.269,
+            field: 0.0,
+            update: IsingUpdate::default(),
+            boundary: Boundary::default(),
+            rng: Rng::new(seed),
+            generation: 0,
+        };
+        ising.randomize();
+        ising
+    }
+    // one Monte Carlo sweep
+    pub fn tick(&mut self) {
+        match self.update {
+            IsingUpdate::Metropolis => {
+                let size = self.width as u64 * self.height as u64;
+                for _ in 0..size {
+                    let idx = self.rng.next_u64() % size;
+                    let row = (idx / self.width as u64) as u32;
+                    let col = (idx % self.width as u64) as u32;
+                    self.attempt_flip(row, col);
+                }
+            }
+            IsingUpdate::Checkerboard => {
+                for parity in 0..2 {
+                    for row in 0..self.height {
+                        for col in 0..self.width {
+                            if (row + col) % 2 == parity {
+                                self.attempt_flip(row, col);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        self.generation += 1;
+    }
+    pub fn tick_n(&mut self, n: u32) {
+        for _ in 0..n {
+            self.tick();
+        }
+    }
+    // infinite-temperature start: every spin up or down with equal odds
+    pub fn randomize(&mut self) {
+        for idx in 0..self.cells.len() {
+            self.cells[idx] = (self.rng.next_u64() & 1) as u8;
+        }
+        self.generation = 0;
+    }
+    // zero-temperature start with every spin aligned
+    pub fn fill(&mut self, spin: Spin) {
+        self.cells.iter_mut().for_each(|c| *c = spin as u8);
+        self.generation = 0;
+    }
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    pub fn temperature(&self) -> f64 {
+        self.temperature
+    }
+    pub fn set_temperature(&mut self, temperature: f64) {
+        self.temperature = temperature.max(0.0);
+    }
+    // external magnetic field favoring up spins when positive
+    pub fn field(&self) -> f64 {
+        self.field
+    }
+    pub fn set_field(&mut self, field: f64) {
+        self.field = field;
+    }
+    pub fn update(&self) -> IsingUpdate {
+        self.update
+    }
+    pub fn set_update(&mut self, update: IsingUpdate) {
+        self.update = update;
+    }
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+    }
+    pub fn get_spin(&self, row: u32, column: u32) -> Spin {
+        Spin::from_state(self.cells[self.get_index(row, column)])
+    }
+    pub fn set_spin(&mut self, row: u32, column: u32, spin: Spin) {
+        let idx = self.get_index(row, column);
+        self.cells[idx] = spin as u8;
+    }
+    // mean spin per site, in [-1, 1]
+    pub fn magnetization(&self) -> f64 {
+        if self.cells.is_empty() {
+            return 0.0;
+        }
+        let total: i64 = (0..self.cells.len()).map(|idx| self.spin(idx)).sum();
+        total as f64 / self.cells.len() as f64
+    }
+    // energy per site, counting each bond once through its right and lower end
+    pub fn energy(&self) -> f64 {
+        if self.cells.is_empty() {
+            return 0.0;
+        }
+        let mut bonds = 0;
+        let mut spins = 0;
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let spin = self.spin(self.get_index(row, col));
+                spins += spin;
+                bonds += IntoIterator::into_iter([(1, 0), (0, 1)])
+                    .filter_map(|(delta_row, delta_col)| {
+                        self.neighbor(row, col, delta_row, delta_col)
+                    })
+                    .map(|neighbor| spin * self.spin(neighbor))
+                    .sum::<i64>();
+            }
+        }
+        (-bonds as f64 - self.field * spins as f64) / self.cells.len() as f64
+    }
+    pub fn cells(&self) -> js_sys::Uint8Array {
+        unsafe { js_sys::Uint8Array::view(&self.cells) }
+    }
+}
@@ -0,0 +1,256 @@
+use crate::rule::{next_state, parse_states, RuleError};
+use std::fmt;
+use std::str::FromStr;
+
+// The Moore neighbors clockwise from the north-west corner. Bit `i` of a
+// neighborhood configuration is the state of RING[i], so rotating the
+// neighborhood by 90 degrees is a 2-bit rotation of the configuration.
+pub(crate) const RING: [(i64, i64); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+];
+const NW: u8 = 1 << 0;
+const N: u8 = 1 << 1;
+const NE: u8 = 1 << 2;
+const E: u8 = 1 << 3;
+const SE: u8 = 1 << 4;
+const S: u8 = 1 << 5;
+const SW: u8 = 1 << 6;
+const W: u8 = 1 << 7;
+
+// Hensel letters for each neighbor count in canonical order, each with one
+// representative configuration; the rest of the class are its rotations and
+// reflections.
+const CLASSES: [&[(char, u8)]; 9] = [
+    &[],
+    &[('c', NW), ('e', N)],
+    &[
+        ('c', NW | NE),
+        ('e', N | E),
+        ('k', NW | E),
+        ('a', NW | N),
+        ('i', N | S),
+        ('n', NW | SE),
+    ],
+    &[
+        ('c', NW | NE | SE),
+        ('e', N | E | S),
+        ('k', NW | E | S),
+        ('a', NW | N | W),
+        ('i', NW | N | NE),
+        ('n', NW | N | SW),
+        ('y', NW | NE | S),
+        ('q', NW | N | SE),
+        ('j', NW | N | E),
+        ('r', NW | N | S),
+    ],
+    &[
+        ('c', NW | NE | SE | SW),
+        ('e', N | E | S | W),
+        ('k', NW | N | E | SW),
+        ('a', NW | N | NE | E),
+        ('i', NW | N | S | SW),
+        ('n', NW | N | NE | SE),
+        ('y', NW | N | SE | SW),
+        ('q', NW | N | SE | W),
+        ('j', NW | N | E | S),
+        ('r', NW | N | E | W),
+        ('t', NW | N | NE | S),
+        ('w', NW | N | E | SE),
+        ('z', NW | N | SE | S),
+    ],
+    &[
+        ('c', NW | N | E | S | W),
+        ('e', NW | N | NE | SE | SW),
+        ('k', NW | N | E | SE | SW),
+        ('a', NW | N | NE | E | SE),
+        ('i', NW | N | NE | E | W),
+        ('n', NW | N | NE | E | S),
+        ('y', NW | N | E | S | SW),
+        ('q', NW | N | E | SE | S),
+        ('j', NW | N | NE | E | SW),
+        ('r', NW | N | NE | SE | S),
+    ],
+    &[
+        ('c', NW | N | NE | E | S | W),
+        ('e', NW | N | NE | E | SE | SW),
+        ('k', NW | N | NE | E | S | SW),
+        ('a', NW | N | NE | E | SE | S),
+        ('i', NW | N | NE | SE | S | SW),
+        ('n', NW | N | E | SE | S | W),
+    ],
+    &[('c', !NW), ('e', !N)],
+    &[],
+];
+
+// left-right mirror: NW <-> NE, W <-> E, SW <-> SE
+fn reflect(config: u8) -> u8 {
+    (0..8)
+        .filter(|i| config & (1 << i) != 0)
+        .fold(0, |mirrored, i| mirrored | 1 << ((10 - i) % 8))
+}
+fn symmetries(config: u8) -> impl Iterator<Item = u8> {
+    IntoIterator::into_iter([config, reflect(config)])
+        .flat_map(|config| (0..4).map(move |quarter| config.rotate_left(2 * quarter)))
+}
+
+// One bit per neighborhood configuration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+struct Configurations([u64; 4]);
+impl Configurations {
+    fn insert(&mut self, config: u8) {
+        self.0[config as usize / 64] |= 1 << (config % 64);
+    }
+    fn contains(&self, config: u8) -> bool {
+        self.0[config as usize / 64] & (1 << (config % 64)) != 0
+    }
+    fn insert_class(&mut self, representative: u8) {
+        for config in symmetries(representative) {
+            self.insert(config);
+        }
+    }
+}
+
+// Isotropic non-totalistic rule in Hensel notation, e.g. "B2-a/S12": each
+// neighbor count may be narrowed to some of its letters, or to all but some
+// after a '-'.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IsotropicRule {
+    birth: Configurations,
+    survival: Configurations,
+    states: u8,
+}
+impl IsotropicRule {
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+    // `config` packs the live neighbors as described for RING
+    pub fn next_state(&self, state: u8, config: u8) -> u8 {
+        next_state(
+            state,
+            self.birth.contains(config),
+            self.survival.contains(config),
+            self.states,
+        )
+    }
+}
+
+fn parse_configurations(spec: &str) -> Result<Configurations, RuleError> {
+    let mut configs = Configurations::default();
+    let mut chars = spec.chars().peekable();
+    while let Some(c) = chars.next() {
+        let count = match c.to_digit(10) {
+            Some(n) if n <= 8 => n as usize,
+            _ => return Err(RuleError::InvalidCount(c)),
+        };
+        let negated = chars.next_if_eq(&'-').is_some();
+        let mut letters = Vec::new();
+        while let Some(letter) = chars.next_if(|c| c.is_ascii_lowercase()) {
+            if !CLASSES[count].iter().any(|&(l, _)| l == letter) {
+                return Err(RuleError::InvalidCount(letter));
+            }
+            letters.push(letter);
+        }
+        if negated && letters.is_empty() {
+            return Err(RuleError::InvalidCount('-'));
+        }
+        if count == 0 || count == 8 {
+            // a single configuration each, with no letters to pick from
+            configs.insert(if count == 0 { 0 } else { u8::MAX });
+            continue;
+        }
+        for &(letter, representative) in CLASSES[count] {
+            if letters.is_empty() || letters.contains(&letter) != negated {
+                configs.insert_class(representative);
+            }
+        }
+    }
+    Ok(configs)
+}
+fn format_configurations(f: &mut fmt::Formatter, configs: &Configurations) -> fmt::Result {
+    if configs.contains(0) {
+        write!(f, "0")?;
+    }
+    for (count, classes) in CLASSES.iter().enumerate().take(8).skip(1) {
+        let (present, absent): (Vec<_>, Vec<_>) = classes
+            .iter()
+            .partition(|&&(_, representative)| configs.contains(representative));
+        if present.is_empty() {
+            continue;
+        }
+        write!(f, "{}", count)?;
+        if absent.is_empty() {
+            continue;
+        }
+        let (prefix, letters) = if absent.len() < present.len() {
+            ("-", absent)
+        } else {
+            ("", present)
+        };
+        write!(f, "{}", prefix)?;
+        for &(letter, _) in letters {
+            write!(f, "{}", letter)?;
+        }
+    }
+    if configs.contains(u8::MAX) {
+        write!(f, "8")?;
+    }
+    Ok(())
+}
+// True when a B/S rulestring uses Hensel letters.
+pub(crate) fn is_isotropic(rule: &str) -> bool {
+    rule.split('/').any(|part| {
+        let mut chars = part.trim().chars();
+        matches!(chars.next(), Some('B' | 'b' | 'S' | 's'))
+            && chars.any(|c| c.is_ascii_alphabetic() || c == '-')
+    })
+}
+impl FromStr for IsotropicRule {
+    type Err = RuleError;
+    // "B2-a/S12" or with a Generations suffix, "B2a/S/C3"
+    fn from_str(rule: &str) -> Result<IsotropicRule, RuleError> {
+        let malformed = || RuleError::Malformed(rule.to_string());
+        let mut birth = None;
+        let mut survival = None;
+        let mut states = None;
+        for part in rule.trim().split('/') {
+            let mut chars = part.chars();
+            match chars.next().map(|c| c.to_ascii_uppercase()) {
+                Some('B') if birth.is_none() => birth = Some(parse_configurations(chars.as_str())?),
+                Some('S') if survival.is_none() => {
+                    survival = Some(parse_configurations(chars.as_str())?)
+                }
+                Some('C') | Some('G') if states.is_none() => {
+                    states = Some(parse_states(chars.as_str())?)
+                }
+                _ => return Err(malformed()),
+            }
+        }
+        match (birth, survival) {
+            (Some(birth), Some(survival)) => Ok(IsotropicRule {
+                birth,
+                survival,
+                states: states.unwrap_or(2),
+            }),
+            _ => Err(malformed()),
+        }
+    }
+}
+impl fmt::Display for IsotropicRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "B")?;
+        format_configurations(f, &self.birth)?;
+        write!(f, "/S")?;
+        format_configurations(f, &self.survival)?;
+        if self.states > 2 {
+            write!(f, "/C{}", self.states)?;
+        }
+        Ok(())
+    }
+}
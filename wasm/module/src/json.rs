@@ -0,0 +1,113 @@
+use crate::neighborhood::MAX_RADIUS;
+use crate::pattern::{self, Pattern, PatternError};
+use crate::snapshot::{Snapshot, MAX_CELLS};
+use crate::{Boundary, Cell, ColorVariant, Neighborhood, TieBreak, Universe};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use wasm_bindgen::prelude::*;
+
+// The shape the universe takes in JSON: the same fields as a binary
+// snapshot, with the cells and colors as RLE of the whole grid so they stay
+// readable.
+#[derive(Serialize, Deserialize)]
+struct UniverseState {
+    width: u32,
+    height: u32,
+    generation: u64,
+    rule: String,
+    neighborhood: Neighborhood,
+    radius: u32,
+    boundary: Boundary,
+    tie_break: TieBreak,
+    color_variant: ColorVariant,
+    cells: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    colors: Option<String>,
+}
+
+fn grid_rle(values: &[u8], width: u32, height: u32, rule: &str, multistate: bool) -> String {
+    let cells = values
+        .iter()
+        .enumerate()
+        .filter(|(_, &value)| value != Cell::Dead as u8)
+        .map(|(idx, &value)| (idx as u32 / width, idx as u32 % width, value))
+        .collect();
+    let grid = Pattern {
+        width,
+        height,
+        cells,
+    };
+    pattern::format_rle(&grid, rule, multistate)
+}
+fn grid_values(rle: &str, width: u32, height: u32) -> Result<Vec<u8>, PatternError> {
+    let mut values = vec![Cell::Dead as u8; (width * height) as usize];
+    for (row, col, value) in pattern::parse_rle(rle, values.len())?.cells {
+        if row < height && col < width {
+            values[(row * width + col) as usize] = value;
+        }
+    }
+    Ok(values)
+}
+
+impl Serialize for Universe {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let rule = self.rule.to_string();
+        let multistate = self.rule.states() > 2;
+        let colors = (self.color_variant != ColorVariant::Off)
+            .then(|| grid_rle(&self.colors, self.width, self.height, &rule, true));
+        UniverseState {
+            width: self.width,
+            height: self.height,
+            generation: self.generation,
+            cells: grid_rle(&self.cells, self.width, self.height, &rule, multistate),
+            rule,
+            neighborhood: self.neighborhood,
+            radius: self.radius,
+            boundary: self.boundary,
+            tie_break: self.tie_break,
+            color_variant: self.color_variant,
+            colors,
+        }
+        .serialize(serializer)
+    }
+}
+impl<'de> Deserialize<'de> for Universe {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let state = UniverseState::deserialize(deserializer)?;
+        let (width, height) = (state.width, state.height);
+        if width as u64 * height as u64 > MAX_CELLS {
+            return Err(D::Error::custom("universe too large"));
+        }
+        if !(1..=MAX_RADIUS).contains(&state.radius) {
+            return Err(D::Error::custom("radius out of range"));
+        }
+        let cells = grid_values(&state.cells, width, height).map_err(D::Error::custom)?;
+        let colors = match state.colors {
+            Some(colors) => grid_values(&colors, width, height).map_err(D::Error::custom)?,
+            None => vec![0; cells.len()],
+        };
+        Ok(Universe::restored(Snapshot {
+            width,
+            height,
+            generation: state.generation,
+            rule: state.rule.parse().map_err(D::Error::custom)?,
+            neighborhood: state.neighborhood,
+            radius: state.radius,
+            boundary: state.boundary,
+            tie_break: state.tie_break,
+            color_variant: state.color_variant,
+            cells,
+            colors,
+        }))
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    pub fn to_json(&self) -> Result<String, JsError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+    pub fn from_json(json: &str) -> Result<Universe, JsError> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
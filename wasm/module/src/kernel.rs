@@ -0,0 +1,39 @@
+use crate::plane::{check_rule, next_alive};
+use crate::{Cell, Rule, RING};
+
+// Next state of a two-state radius 1 Moore rule for each of the 512 ways a
+// 3 x 3 window can be filled. Bits 6 to 8 of the index hold the window's
+// left column, 3 to 5 the middle and 0 to 2 the right, top row lowest, so
+// sliding the window one column right is a shift by 3 and one new column.
+#[derive(Clone, Debug)]
+pub(crate) struct Table([u8; 512]);
+impl Table {
+    // None for rules that need more than the 3 x 3 window and two states.
+    pub(crate) fn new(rule: &Rule) -> Option<Table> {
+        check_rule(rule).ok()?;
+        let mut table = [Cell::Dead as u8; 512];
+        for (window, next) in table.iter_mut().enumerate() {
+            let alive = |row: i64, col: i64| window >> ((1 - col) * 3 + row + 1) & 1 == 1;
+            let mut config = 0u8;
+            for (bit, &(delta_row, delta_col)) in RING.iter().enumerate() {
+                config |= (alive(delta_row, delta_col) as u8) << bit;
+            }
+            *next = next_alive(rule, alive(0, 0), config) as u8;
+        }
+        Some(Table(table))
+    }
+    // Fills `next` from a grid padded the way MooreCounts::pad() does it.
+    pub(crate) fn step(&self, padded: &[u8], width: u32, height: u32, next: &mut [u8]) {
+        let (width, padded_width) = (width as usize, width as usize + 2);
+        for row in 0..height as usize {
+            let above = &padded[row * padded_width..];
+            let (middle, below) = (&above[padded_width..], &above[2 * padded_width..]);
+            let column = |col: usize| (above[col] | middle[col] << 1 | below[col] << 2) as usize;
+            let mut window = column(0) << 3 | column(1);
+            for (col, next) in next[row * width..(row + 1) * width].iter_mut().enumerate() {
+                window = (window << 3 | column(col + 2)) & 0x1ff;
+                *next = self.0[window];
+            }
+        }
+    }
+}
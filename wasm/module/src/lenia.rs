@@ -0,0 +1,254 @@
+use crate::rng::Rng;
+use crate::Boundary;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GrowthFunction {
+    // 2 exp(-(u - mu)^2 / 2 sigma^2) - 1
+    Gaussian = 0,
+    // 2 (1 - (u - mu)^2 / 9 sigma^2)^4 - 1 inside |u - mu| < 3 sigma
+    Polynomial = 1,
+    // +1 within sigma of mu, -1 elsewhere
+    Step = 2,
+}
+impl GrowthFunction {
+    fn apply(self, u: f32, mu: f32, sigma: f32) -> f32 {
+        let d = u - mu;
+        match self {
+            GrowthFunction::Gaussian => 2.0 * (-d * d / (2.0 * sigma * sigma)).exp() - 1.0,
+            GrowthFunction::Polynomial => {
+                let x = 1.0 - d * d / (9.0 * sigma * sigma);
+                2.0 * x.max(0.0).powi(4) - 1.0
+            }
+            GrowthFunction::Step => {
+                if d.abs() <= sigma {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+        }
+    }
+}
+// smooth bump on (0, 1) peaking at 1/2
+fn kernel_core(r: f32) -> f32 {
+    if r <= 0.0 || r >= 1.0 {
+        0.0
+    } else {
+        (4.0 - 1.0 / (r * (1.0 - r))).exp()
+    }
+}
+
+// Lenia: a continuous-state, continuous-time generalization of Life. Every
+// cell holds a value in [0, 1]; each tick the world is convolved with a
+// ring-shaped kernel of `radius` cells and moved `dt` along the growth
+// function of that potential.
+#[wasm_bindgen]
+pub struct Lenia {
+    width: u32,
+    height: u32,
+    field: Vec<f32>,
+    // field mapped to 0-255, refreshed every tick
+    cells: Vec<u8>,
+    radius: u32,
+    // relative heights of the kernel's concentric rings
+    peaks: Vec<f32>,
+    mu: f32,
+    sigma: f32,
+    dt: f32,
+    growth: GrowthFunction,
+    // non-zero kernel weights as (delta_row, delta_col, weight), summing to 1
+    kernel: Vec<(i64, i64, f32)>,
+    boundary: Boundary,
+    generation: u64,
+}
+#[wasm_bindgen]
+impl Lenia {
+    fn get_index(&self, row: u32, column: u32) -> usize {
+        (row * self.width + column) as usize
+    }
+    fn rebuild_kernel(&mut self) {
+        let r = self.radius as i64;
+        let rings = self.peaks.len() as f32;
+        let mut kernel = Vec::new();
+        for delta_row in -r..=r {
+            for delta_col in -r..=r {
+                let distance = ((delta_row * delta_row + delta_col * delta_col) as f32).sqrt()
+                    / self.radius as f32;
+                if distance >= 1.0 {
+                    continue;
+                }
+                let ring = distance * rings;
+                let peak = self.peaks[(ring as usize).min(self.peaks.len() - 1)];
+                let weight = peak * kernel_core(ring.fract());
+                if weight > 0.0 {
+                    kernel.push((delta_row, delta_col, weight));
+                }
+            }
+        }
+        let total: f32 = kernel.iter().map(|&(_, _, weight)| weight).sum();
+        if total > 0.0 {
+            kernel
+                .iter_mut()
+                .for_each(|(_, _, weight)| *weight /= total);
+        }
+        self.kernel = kernel;
+    }
+    fn potential(&self, row: u32, column: u32) -> f32 {
+        let mut sum = 0.0;
+        for &(delta_row, delta_col, weight) in self.kernel.iter() {
+            if let (Some(row), Some(col)) = (
+                self.boundary.resolve(row as i64 + delta_row, self.height),
+                self.boundary.resolve(column as i64 + delta_col, self.width),
+            ) {
+                sum += weight * self.field[self.get_index(row, col)];
+            }
+        }
+        sum
+    }
+    fn refresh_cells(&mut self) {
+        for (cell, &value) in self.cells.iter_mut().zip(self.field.iter()) {
+            *cell = (value * 255.0) as u8;
+        }
+    }
+    // Orbium's parameters (R = 13, mu = 0.15, sigma = 0.015, T = 10) over a
+    // random patch in the middle of the world.
+    pub fn new(width: u32, height: u32) -> Lenia {
+        let size = (width * height) as usize;
+        let mut lenia = Lenia {
+            width,
+            height,
+            field: vec![0.0; size],
+            cells: vec![0; size],
+            radius: 13,
+            peaks: vec![1.0],
+            mu: 0.15,
+            sigma: 0.015,
+            dt: 0.1,
+            growth: GrowthFunction::Gaussian,
+            kernel: Vec::new(),
+            boundary: Boundary::default(),
+            generation: 0,
+        };
+        lenia.rebuild_kernel();
+        lenia.randomize(0);
+        lenia
+    }
+    pub fn tick(&mut self) {
+        let mut next = self.field.clone();
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let idx = self.get_index(row, col);
+                let growth = self
+                    .growth
+                    .apply(self.potential(row, col), self.mu, self.sigma);
+                next[idx] = (self.field[idx] + self.dt * growth).clamp(0.0, 1.0);
+            }
+        }
+        self.field = next;
+        self.refresh_cells();
+        self.generation += 1;
+    }
+    pub fn tick_n(&mut self, n: u32) {
+        for _ in 0..n {
+            self.tick();
+        }
+    }
+    // Clears the world and fills a centered square, a few kernel radii wide,
+    // with uniform random values.
+    pub fn randomize(&mut self, seed: u64) {
+        self.field.iter_mut().for_each(|value| *value = 0.0);
+        let mut rng = Rng::new(seed);
+        let side = (self.radius * 4).min(self.width).min(self.height);
+        let top = (self.height - side) / 2;
+        let left = (self.width - side) / 2;
+        for row in top..top + side {
+            for col in left..left + side {
+                let idx = self.get_index(row, col);
+                self.field[idx] = rng.next_f32();
+            }
+        }
+        self.refresh_cells();
+        self.generation = 0;
+    }
+    pub fn clear(&mut self) {
+        self.field.iter_mut().for_each(|value| *value = 0.0);
+        self.refresh_cells();
+        self.generation = 0;
+    }
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    pub fn radius(&self) -> u32 {
+        self.radius
+    }
+    pub fn set_radius(&mut self, radius: u32) {
+        self.radius = radius.max(1);
+        self.rebuild_kernel();
+    }
+    pub fn peaks(&self) -> Vec<f32> {
+        self.peaks.clone()
+    }
+    // Ring heights from the center out, e.g. [1] for a single ring or
+    // [0.5, 1, 0.667] for three.
+    pub fn set_peaks(&mut self, peaks: &[f32]) {
+        self.peaks = if peaks.is_empty() {
+            vec![1.0]
+        } else {
+            peaks.to_vec()
+        };
+        self.rebuild_kernel();
+    }
+    pub fn mu(&self) -> f32 {
+        self.mu
+    }
+    pub fn sigma(&self) -> f32 {
+        self.sigma
+    }
+    pub fn set_growth(&mut self, growth: GrowthFunction, mu: f32, sigma: f32) {
+        self.growth = growth;
+        self.mu = mu;
+        self.sigma = sigma;
+    }
+    pub fn growth_function(&self) -> GrowthFunction {
+        self.growth
+    }
+    pub fn dt(&self) -> f32 {
+        self.dt
+    }
+    pub fn set_dt(&mut self, dt: f32) {
+        self.dt = dt;
+    }
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+    }
+    pub fn get_value(&self, row: u32, column: u32) -> f32 {
+        self.field[self.get_index(row, column)]
+    }
+    pub fn set_value(&mut self, row: u32, column: u32, value: f32) {
+        let idx = self.get_index(row, column);
+        self.field[idx] = value.clamp(0.0, 1.0);
+        self.cells[idx] = (self.field[idx] * 255.0) as u8;
+    }
+    // Total of all cell values.
+    pub fn mass(&self) -> f32 {
+        self.field.iter().sum()
+    }
+    pub fn field(&self) -> js_sys::Float32Array {
+        unsafe { js_sys::Float32Array::view(&self.field) }
+    }
+    pub fn cells(&self) -> js_sys::Uint8Array {
+        unsafe { js_sys::Uint8Array::view(&self.cells) }
+    }
+}
@@ -1,5 +1,108 @@
-use std::mem;
+mod apgcode;
+mod autosave;
+mod base64;
+mod boundary;
+mod cave;
+mod census;
+mod clip;
+mod colors;
+mod components;
+mod counts;
+mod cyclic;
+mod draw;
+mod drift;
+mod elementary;
+mod falling_sand;
+mod files;
+mod forest_fire;
+mod gray_scott;
+mod hashlife;
+mod history;
+mod ising;
+mod isotropic;
+#[cfg(feature = "serde")]
+mod json;
+mod kernel;
+mod lenia;
+mod ltl;
+mod macrocell;
+mod majority;
+mod neighborhood;
+mod patch;
+mod pattern;
+mod period;
+mod plane;
+mod renderer;
+mod replay;
+mod rng;
+mod rock_paper_scissors;
+mod rule;
+mod sandpile;
+mod series;
+mod slots;
+mod smoothlife;
+mod snapshot;
+mod soup;
+mod sparse;
+mod stats;
+mod tiles;
+mod timeline;
+mod turmite;
+mod wator;
+mod wireworld;
+
+use autosave::Autosave;
+pub use boundary::Boundary;
+pub use cave::Cave;
+pub use census::CensusEntry;
+pub use clip::{BlendMode, Clip};
+use colors::inherited_color;
+pub use colors::ColorVariant;
+use counts::MooreCounts;
+pub use cyclic::Cyclic;
+use drift::Drift;
+pub use elementary::Elementary;
+pub use falling_sand::{FallingSand, Material};
+pub use files::FileFormat;
+pub use forest_fire::{ForestCell, ForestFire};
+pub use gray_scott::GrayScott;
+pub use hashlife::HashLife;
+use history::History;
+pub use ising::{Ising, IsingUpdate, Spin};
+pub use isotropic::IsotropicRule;
+use isotropic::RING;
+use kernel::Table;
+pub use lenia::{GrowthFunction, Lenia};
+pub use ltl::LtlRule;
+use majority::majority_state;
+pub use majority::TieBreak;
+pub use neighborhood::Neighborhood;
+use neighborhood::{hex_center, neighbor_indices, MAX_RADIUS};
+use patch::Patch;
+use pattern::Pattern;
+pub use pattern::{Axis, PatternError, PatternInfo, Transform};
+use period::Periods;
+use replay::{Event, Recorder, Start};
+use rng::Rng;
+pub use rock_paper_scissors::RockPaperScissors;
+pub use rule::{LifeLikeRule, Rule, RuleError, RulePreset};
+pub use sandpile::Sandpile;
+use series::Series;
+pub use slots::{delete_snapshot, fetch_snapshot, list_snapshots, store_snapshot};
+pub use smoothlife::SmoothLife;
+pub use snapshot::SnapshotError;
+use snapshot::{Snapshot, MAX_CELLS};
+pub use soup::{SoupResult, Symmetry};
+pub use sparse::SparseUniverse;
+pub use stats::Stats;
+use tiles::Tiles;
+use timeline::{Before, Timeline};
+pub use turmite::{Direction, TurmiteError, Turmites};
 use wasm_bindgen::prelude::*;
+pub use wator::{WaTor, WaTorCell};
+pub use wireworld::{Wireworld, WireworldCell};
+
+use std::fmt;
 
 #[wasm_bindgen]
 #[repr(u8)]
@@ -8,79 +111,2167 @@ pub enum Cell {
     Dead = 0,
     Alive = 1,
 }
+// What the last tick found, so an auto-run loop knows when to stop.
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Status {
+    #[default]
+    Running = 0,
+    // no live cells left
+    Extinct = 1,
+    // the tick changed nothing
+    Still = 2,
+}
+// Number of cells that fit along `pixels` when every cell is `cell_size` pixels
+// wide and followed by a 1px grid line, plus the leading grid line.
+fn cells_for_pixels(pixels: u32, cell_size: u32) -> u32 {
+    pixels.saturating_sub(1) / cell_size.saturating_add(1)
+}
+// Every grid is held to the cell count a snapshot may claim.
+fn check_size(width: u32, height: u32) -> Result<(), JsError> {
+    if width as u64 * height as u64 > MAX_CELLS {
+        return Err(JsError::new("universe too large"));
+    }
+    Ok(())
+}
+fn clipboard() -> Result<web_sys::Clipboard, JsError> {
+    let window =
+        web_sys::window().ok_or_else(|| JsError::new("no window to reach the clipboard"))?;
+    Ok(window.navigator().clipboard())
+}
+// Pages of wasm memory, which only grows.
+#[cfg(target_arch = "wasm32")]
+fn memory_pages() -> usize {
+    core::arch::wasm32::memory_size(0)
+}
+#[cfg(not(target_arch = "wasm32"))]
+fn memory_pages() -> usize {
+    0
+}
+fn performance() -> Result<web_sys::Performance, JsError> {
+    web_sys::window()
+        .and_then(|window| window.performance())
+        .ok_or_else(|| JsError::new("no performance timer"))
+}
+// performance.mark() and measure() for the phases of a tick, while profiling
+fn mark(profiler: Option<&web_sys::Performance>, mark: &str) {
+    if let Some(performance) = profiler {
+        let _ = performance.mark(mark);
+    }
+}
+fn measure(profiler: Option<&web_sys::Performance>, measure: &str, start: &str, end: &str) {
+    if let Some(performance) = profiler {
+        let _ = performance.measure_with_start_mark_and_end_mark(measure, start, end);
+    }
+}
 #[wasm_bindgen]
 pub struct Universe {
     width: u32,
     height: u32,
-    cells: Vec<Cell>,
+    // one state per cell: Cell::Dead, Cell::Alive, or a Generations dying state
+    cells: Vec<u8>,
+    // consecutive generations each cell has been alive, 0 for any other state
+    ages: Vec<u16>,
+    // generations since each cell last changed state, tick or edit
+    quiet: Vec<u16>,
+    // color of each live cell under a ColorVariant, 0 for any other state
+    colors: Vec<u8>,
+    color_variant: ColorVariant,
+    generation: u64,
+    boundary: Boundary,
+    rule: Rule,
+    neighborhood: Neighborhood,
+    radius: u32,
+    neighbor_offsets: Vec<(i64, i64)>,
+    tie_break: TieBreak,
+    // chance that a majority-vote cell ends up in the minority state
+    noise: f32,
+    // chances that a birth or survival the rule calls for actually happens
+    birth_probability: f32,
+    survival_probability: f32,
+    rng: Rng,
+    freeze_when_stable: bool,
+    // the timer ticks leave performance marks on, while profiling
+    profiler: Option<web_sys::Performance>,
+    // set once a tick changed nothing or only flipped back to the generation
+    // before while freeze_when_stable was on, and cleared again by any edit
+    frozen: bool,
+    // the generation before `cells`, kept only while freeze_when_stable is on
+    previous: Vec<u8>,
+    // spare buffer tick() builds the next generation in, so it doesn't
+    // allocate one every time
+    next: Vec<u8>,
+    // where `cells` was and how big wasm memory was when view_version() last
+    // looked, and how many times either has changed
+    view: (usize, usize, usize),
+    view_version: u32,
+    // pixels render_rgba() last drew
+    rgba: Vec<u8>,
+    // scratch for step_cells(): the cells it steps, and which of them it has
+    // already listed
+    active: Vec<u32>,
+    marked: Vec<bool>,
+    // scratch for counting Moore neighbors a row at a time
+    moore_counts: MooreCounts,
+    // the rule as a lookup table, when it is a two-state radius 1 Moore rule
+    table: Option<Table>,
+    // the parts of the grid the next tick has to look at, all of it after
+    // any edit
+    tiles: Tiles,
+    // live cells, kept up to date by every tick and edit
+    population: u32,
+    // what the last tick found, back to Running after any edit
+    status: Status,
+    // for detected_period(), cleared by any edit
+    periods: Periods,
+    // cells the last tick brought to life and took out of it
+    births: u32,
+    deaths: u32,
+    // indices of the cells the last tick changed
+    changed: Vec<u32>,
+    // population after each of the latest ticks
+    population_history: Series,
+    // centers of mass after the latest ticks, cut off by any edit
+    drift: Drift,
+    // generations each cell has been alive since enable_heatmap()
+    heat: Option<Vec<u32>>,
+    history: History,
+    // ticks that rewind() can take back, cut off by any edit
+    timeline: Timeline,
+    autosave: Option<Autosave>,
+    recorder: Option<Recorder>,
 }
 #[wasm_bindgen]
 impl Universe {
     fn get_index(&self, row: u32, column: u32) -> usize {
         (row * self.width + column) as usize
     }
-    fn live_neighbor_count(&self, row: u32, column: u32) -> u8 {
+    // get_index() of a cell on the grid, None for coordinates off it
+    fn checked_index(&self, row: u32, column: u32) -> Option<usize> {
+        (row < self.height && column < self.width).then(|| self.get_index(row, column))
+    }
+    fn neighbor_index(
+        &self,
+        row: u32,
+        column: u32,
+        delta_row: i64,
+        delta_col: i64,
+    ) -> Option<usize> {
+        let neighbor_row = self.boundary.resolve(row as i64 + delta_row, self.height)?;
+        let neighbor_col = self
+            .boundary
+            .resolve(column as i64 + delta_col, self.width)?;
+        Some(self.get_index(neighbor_row, neighbor_col))
+    }
+    fn live_neighbor_count(&self, row: u32, column: u32) -> u32 {
         let mut count = 0;
-        for delta_row in [self.height - 1, 0, 1].iter().cloned() {
-            for delta_col in [self.width - 1, 0, 1].iter().cloned() {
-                if delta_row == 0 && delta_col == 0 {
-                    continue;
-                }
-                let neighbor_row = (row + delta_row) % self.height;
-                let neighbor_col = (column + delta_col) % self.width;
-                let idx = self.get_index(neighbor_row, neighbor_col);
-                count += self.cells[idx] as u8;
+        for &(delta_row, delta_col) in self.neighbor_offsets.iter() {
+            if let Some(idx) = self.neighbor_index(row, column, delta_row, delta_col) {
+                count += (self.cells[idx] == Cell::Alive as u8) as u32;
             }
         }
         count
     }
-    pub fn tick(&mut self) {
-        let mut next = self.cells.clone();
+    // live cells and cells on the grid among the cell and its neighbors
+    fn vote_count(&self, row: u32, column: u32) -> (u32, u32) {
+        let mut alive = (self.cells[self.get_index(row, column)] == Cell::Alive as u8) as u32;
+        let mut total = 1;
+        for &(delta_row, delta_col) in self.neighbor_offsets.iter() {
+            if let Some(idx) = self.neighbor_index(row, column, delta_row, delta_col) {
+                alive += (self.cells[idx] == Cell::Alive as u8) as u32;
+                total += 1;
+            }
+        }
+        (alive, total)
+    }
+    fn neighbor_configuration(&self, row: u32, column: u32) -> u8 {
+        let mut config = 0;
+        for (bit, &(delta_row, delta_col)) in RING.iter().enumerate() {
+            if let Some(idx) = self.neighbor_index(row, column, delta_row, delta_col) {
+                config |= ((self.cells[idx] == Cell::Alive as u8) as u8) << bit;
+            }
+        }
+        config
+    }
+    // too many live cells for looking for the active ones to pay off
+    fn crowded(&self) -> bool {
+        self.population as usize * 8 > self.cells.len()
+    }
+    // Whether a tick should step the whole grid in one go rather than cell
+    // by cell: when most tiles are awake and either a dead cell with no live
+    // neighbors comes alive, SIMD makes counting every cell cheap, or the
+    // grid is crowded.
+    fn dense(&self, quiescent: bool) -> bool {
+        self.tiles.mostly_awake() && (!quiescent || counts::SIMD || self.crowded())
+    }
+    // Sets the cells of `next` that can change to what `step` says. When
+    // `quiescent`, that is when a dead cell with no live neighbors stays
+    // dead, and the grid isn't crowded, only the non-dead cells and their
+    // neighbors are stepped. Otherwise the cells of every awake tile are.
+    // `ring` is for rules that look at the radius 1 Moore ring rather than
+    // the neighborhood. A mirrored edge can fold a non-square neighborhood
+    // onto cells it doesn't reach back from, so that case goes by the tiles.
+    fn step_cells(
+        &mut self,
+        next: &mut [u8],
+        quiescent: bool,
+        ring: bool,
+        step: impl Fn(&Universe, u32, u32, usize) -> u8,
+    ) {
+        let folds = self.boundary == Boundary::Mirror && self.neighborhood != Neighborhood::Moore;
+        if !quiescent || self.crowded() || (folds && !ring) {
+            for (rows, cols) in self.tiles.awake() {
+                for row in rows {
+                    for col in cols.clone() {
+                        let idx = self.get_index(row, col);
+                        next[idx] = step(self, row, col, idx);
+                    }
+                }
+            }
+            return;
+        }
+        let mut active = std::mem::take(&mut self.active);
+        let mut marked = std::mem::take(&mut self.marked);
+        marked.resize(self.cells.len(), false);
+        let offsets: &[(i64, i64)] = if ring { &RING } else { &self.neighbor_offsets };
+        for (idx, _) in self
+            .cells
+            .iter()
+            .enumerate()
+            .filter(|(_, &state)| state != Cell::Dead as u8)
+        {
+            let (row, col) = (idx as u32 / self.width, idx as u32 % self.width);
+            // the cells that have this one among their neighbors
+            let affected = offsets.iter().filter_map(|&(delta_row, delta_col)| {
+                self.neighbor_index(row, col, -delta_row, -delta_col)
+            });
+            for affected in std::iter::once(idx).chain(affected) {
+                if !marked[affected] {
+                    marked[affected] = true;
+                    active.push(affected as u32);
+                }
+            }
+        }
+        for &idx in active.iter() {
+            let (row, col) = (idx / self.width, idx % self.width);
+            next[idx as usize] = step(self, row, col, idx as usize);
+            marked[idx as usize] = false;
+        }
+        active.clear();
+        self.active = active;
+        self.marked = marked;
+    }
+    // Vetoes each birth and survival in `next` that loses its coin flip. A
+    // vetoed survivor dies the way it would have without survival, so
+    // Generations cells start dying instead.
+    fn apply_probabilities(&mut self, next: &mut [u8]) {
+        const DEAD: u8 = Cell::Dead as u8;
+        const ALIVE: u8 = Cell::Alive as u8;
+        let states = self.rule.states();
+        for (&state, next_state) in self.cells.iter().zip(next.iter_mut()) {
+            match (state, *next_state) {
+                (DEAD, ALIVE) if self.rng.next_f32() >= self.birth_probability => {
+                    *next_state = DEAD
+                }
+                (ALIVE, ALIVE) if self.rng.next_f32() >= self.survival_probability => {
+                    *next_state = rule::next_state(ALIVE, false, false, states)
+                }
+                _ => {}
+            }
+        }
+    }
+    // Runs `edit` as a single undo step.
+    fn edit<T>(&mut self, edit: impl FnOnce(&mut Universe) -> T) -> T {
+        self.history.begin();
+        let result = edit(self);
+        self.history.end(&self.cells);
+        self.autosave_if_due();
+        result
+    }
+    // A failed autosave, say over the storage quota, is dropped rather than
+    // failing the tick or edit that set it off.
+    fn autosave_if_due(&mut self) {
+        if self.history.is_open() {
+            return;
+        }
+        let key = match self.autosave.as_mut() {
+            Some(autosave) if autosave.is_due() => autosave.saved(),
+            _ => return,
+        };
+        let _ = autosave::write(&key, &self.to_share_string());
+    }
+    // set_state without the undo history
+    fn write(&mut self, idx: usize, state: u8) {
+        self.thaw();
+        self.population -= (self.cells[idx] == Cell::Alive as u8) as u32;
+        let before = self.cells[idx];
+        self.cells[idx] = if state < self.rule.states() {
+            state
+        } else {
+            Cell::Dead as u8
+        };
+        if self.cells[idx] != before {
+            self.quiet[idx] = 0;
+        }
+        self.population += (self.cells[idx] == Cell::Alive as u8) as u32;
+        if self.cells[idx] != Cell::Alive as u8 {
+            self.ages[idx] = 0;
+            self.colors[idx] = 0;
+        } else if self.ages[idx] == 0 {
+            self.ages[idx] = 1;
+            self.colors[idx] = (self.color_variant != ColorVariant::Off) as u8;
+        }
+    }
+    fn restore(&mut self, states: Option<Vec<(usize, u8)>>) -> bool {
+        let states = match states {
+            Some(states) => states,
+            None => return false,
+        };
+        for (idx, state) in states {
+            self.write(idx, state);
+        }
+        true
+    }
+    // Called on every edit, which also leaves nothing for rewind() to go
+    // back through.
+    fn thaw(&mut self) {
+        self.frozen = false;
+        self.status = Status::Running;
+        self.periods.clear();
+        self.previous.clear();
+        self.timeline.clear();
+        self.drift.clear();
+        self.tiles.wake_all();
+    }
+    fn record_tick(&mut self, next: &[u8]) {
+        if self.timeline.limit() == 0 {
+            return;
+        }
+        let changed = (0..next.len())
+            .filter(|&idx| self.cells[idx] != next[idx])
+            .map(|idx| Before {
+                index: idx as u32,
+                state: self.cells[idx],
+                color: self.colors[idx],
+                age: self.ages[idx],
+            })
+            .collect();
+        self.timeline.push(changed);
+    }
+    // Recomputes the ages, and the population with them, after a bulk
+    // change to the cells, which counts as every cell just changing.
+    fn reset_ages(&mut self) {
+        self.ages = self
+            .cells
+            .iter()
+            .map(|&state| (state == Cell::Alive as u8) as u16)
+            .collect();
+        self.quiet = vec![0; self.cells.len()];
+        self.recount();
+    }
+    fn recount(&mut self) {
+        self.population = self
+            .cells
+            .iter()
+            .filter(|&&state| state == Cell::Alive as u8)
+            .count() as u32;
+    }
+    // Gives live cells random colors, or all the first color without `rng`.
+    fn reset_colors(&mut self, mut rng: Option<&mut Rng>) {
+        let colors = self.color_variant.colors();
+        for (color, &state) in self.colors.iter_mut().zip(self.cells.iter()) {
+            *color = match rng.as_mut() {
+                _ if colors == 0 || state != Cell::Alive as u8 => 0,
+                Some(rng) => (rng.next_u64() % colors as u64) as u8 + 1,
+                None => 1,
+            };
+        }
+    }
+    fn next_colors(&self, next: &[u8]) -> Vec<u8> {
+        let mut colors = vec![0; next.len()];
         for row in 0..self.height {
             for col in 0..self.width {
                 let idx = self.get_index(row, col);
-                let cell = self.cells[idx];
-                let live_neighbors = self.live_neighbor_count(row, col);
-                let next_cell = match (cell, live_neighbors) {
-                    (Cell::Alive, x) if x < 2 => Cell::Dead,
-                    (Cell::Alive, 2) | (Cell::Alive, 3) => Cell::Alive,
-                    (Cell::Alive, x) if x > 3 => Cell::Dead,
-                    (Cell::Dead, 3) => Cell::Alive,
-                    (otherwise, _) => otherwise,
+                if next[idx] != Cell::Alive as u8 {
+                    continue;
+                }
+                colors[idx] = if self.cells[idx] == Cell::Alive as u8 {
+                    self.colors[idx]
+                } else {
+                    let parents = self
+                        .neighbor_offsets
+                        .iter()
+                        .filter_map(|&(delta_row, delta_col)| {
+                            self.neighbor_index(row, col, delta_row, delta_col)
+                        })
+                        .filter(|&neighbor| self.cells[neighbor] == Cell::Alive as u8)
+                        .map(|neighbor| self.colors[neighbor]);
+                    inherited_color(parents, self.color_variant.colors())
                 };
-                next[idx] = next_cell;
             }
         }
-        self.cells = next;
+        colors
+    }
+    // Sets the live cells of `pattern` with its top-left corner at (row,
+    // column), leaving the rest of the grid alone. Cells that fall off the
+    // grid wrap, mirror or get dropped as the boundary says.
+    fn stamp(&mut self, pattern: &Pattern, row: u32, column: u32) {
+        self.edit(|universe| {
+            for &(pattern_row, pattern_col, state) in pattern.cells.iter() {
+                universe.place(
+                    row as i64 + pattern_row as i64,
+                    column as i64 + pattern_col as i64,
+                    state,
+                );
+            }
+        })
+    }
+    // Pastes the bounding box of `pattern` at (row, column), combining each
+    // of its cells with the one under it. Only the grid-sized top-left part
+    // of the box is pasted, so a huge box doesn't wrap onto itself or cost
+    // more than the grid does.
+    fn blend(&mut self, pattern: &Pattern, row: u32, column: u32, mode: BlendMode) {
+        let (width, height) = (
+            pattern.width.min(self.width),
+            pattern.height.min(self.height),
+        );
+        let mut pasted = vec![Cell::Dead as u8; width as usize * height as usize];
+        for &(pattern_row, pattern_col, state) in pattern.cells.iter() {
+            if pattern_row < height && pattern_col < width {
+                pasted[(pattern_row * width + pattern_col) as usize] = state;
+            }
+        }
+        self.edit(|universe| {
+            for pattern_row in 0..height {
+                for pattern_col in 0..width {
+                    let target_row = universe
+                        .boundary
+                        .resolve(row as i64 + pattern_row as i64, universe.height);
+                    let target_col = universe
+                        .boundary
+                        .resolve(column as i64 + pattern_col as i64, universe.width);
+                    if let (Some(target_row), Some(target_col)) = (target_row, target_col) {
+                        let current = universe.get_state(target_row, target_col);
+                        let state = pasted[(pattern_row * width + pattern_col) as usize];
+                        universe.set_state(target_row, target_col, mode.blend(current, state));
+                    }
+                }
+            }
+        })
+    }
+    // set_state for a coordinate that may lie off the grid
+    fn place(&mut self, row: i64, column: i64, state: u8) {
+        let target_row = self.boundary.resolve(row, self.height);
+        let target_col = self.boundary.resolve(column, self.width);
+        if let (Some(target_row), Some(target_col)) = (target_row, target_col) {
+            self.set_state(target_row, target_col, state);
+        }
+    }
+    // Replaces a rectangle with a transformed copy of itself. Rotations by a
+    // quarter turn are only in place for squares.
+    fn transform_region(
+        &mut self,
+        row: u32,
+        column: u32,
+        width: u32,
+        height: u32,
+        transform: Transform,
+    ) {
+        self.edit(|universe| {
+            let mut clip = universe.cut_region(row, column, width, height);
+            clip.pattern = clip.pattern.transformed(transform);
+            universe.stamp(&clip.pattern, row, column);
+        })
+    }
+    // The rule, provided it can run patterns on an unbounded plane.
+    fn plane_rule(&self) -> Result<Rule, PatternError> {
+        if self.neighborhood != Neighborhood::Moore || self.radius != 1 {
+            return Err(PatternError::UnsupportedRule(self.rule.to_string()));
+        }
+        Ok(self.rule)
+    }
+    // Every non-dead cell, cropped to their bounding box.
+    fn snapshot(&self) -> Pattern {
+        let mut pattern = Pattern::default();
+        let live = |idx: &usize| self.cells[*idx] != Cell::Dead as u8;
+        let (rows, cols): (Vec<u32>, Vec<u32>) = (0..self.cells.len())
+            .filter(live)
+            .map(|idx| (idx as u32 / self.width, idx as u32 % self.width))
+            .unzip();
+        let (top, left) = match (rows.iter().min(), cols.iter().min()) {
+            (Some(&top), Some(&left)) => (top, left),
+            _ => return pattern,
+        };
+        pattern.height = rows.iter().max().map_or(0, |&bottom| bottom - top + 1);
+        pattern.width = cols.iter().max().map_or(0, |&right| right - left + 1);
+        for (idx, (row, col)) in (0..self.cells.len())
+            .filter(live)
+            .zip(rows.into_iter().zip(cols))
+        {
+            pattern.cells.push((row - top, col - left, self.cells[idx]));
+        }
+        pattern
+    }
+    fn components(&self, connectivity: Neighborhood) -> (Vec<u32>, u32) {
+        components::label(
+            &self.cells,
+            self.width,
+            self.height,
+            self.boundary,
+            connectivity,
+            1,
+        )
+    }
+    // (row, column) mean of the live cells
+    fn center(&self) -> Option<(f64, f64)> {
+        if self.population == 0 {
+            return None;
+        }
+        let (mut rows, mut cols) = (0.0, 0.0);
+        for (idx, _) in self
+            .cells
+            .iter()
+            .enumerate()
+            .filter(|(_, &state)| state == Cell::Alive as u8)
+        {
+            rows += (idx as u32 / self.width) as f64;
+            cols += (idx as u32 % self.width) as f64;
+        }
+        let population = self.population as f64;
+        Some((rows / population, cols / population))
+    }
+    // Replaces everything with `pattern`, centered on the grid.
+    fn load_pattern(&mut self, pattern: &Pattern) {
+        self.edit(|universe| {
+            universe.fill(Cell::Dead);
+            let row = universe.height.saturating_sub(pattern.height) / 2;
+            let column = universe.width.saturating_sub(pattern.width) / 2;
+            universe.stamp(pattern, row, column);
+        })
+    }
+    // Logs edits since the last event and then `event`, when recording.
+    fn record(&mut self, event: Event) {
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.edits(&self.cells, &self.colors);
+            recorder.event(event);
+        }
+    }
+    // For after an event that changes cells itself, so replaying it will too.
+    fn record_sync(&mut self) {
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.sync(&self.cells, &self.colors);
+        }
+    }
+    fn import_bytes(&mut self, bytes: &[u8]) -> Result<(), JsError> {
+        if Snapshot::is_snapshot(bytes) {
+            let autosave = self.autosave.take();
+            *self = Universe::load(bytes)?;
+            self.autosave = autosave;
+            return Ok(());
+        }
+        let text = std::str::from_utf8(bytes)
+            .map_err(|_| JsError::new("file is neither a snapshot nor a pattern"))?;
+        let pattern = pattern::parse_any(text, self.cells.len())?;
+        self.load_pattern(&pattern);
+        Ok(())
+    }
+    fn saved(&self) -> Snapshot {
+        Snapshot {
+            width: self.width,
+            height: self.height,
+            generation: self.generation,
+            rule: self.rule,
+            neighborhood: self.neighborhood,
+            radius: self.radius,
+            boundary: self.boundary,
+            tie_break: self.tie_break,
+            color_variant: self.color_variant,
+            cells: self.cells.clone(),
+            colors: self.colors.clone(),
+        }
+    }
+    fn restored(snapshot: Snapshot) -> Universe {
+        let mut universe = Universe::sized(snapshot.width, snapshot.height);
+        universe.apply_rule(snapshot.rule);
+        universe.set_neighborhood(snapshot.neighborhood, snapshot.radius);
+        universe.boundary = snapshot.boundary;
+        universe.tie_break = snapshot.tie_break;
+        universe.color_variant = snapshot.color_variant;
+        universe.generation = snapshot.generation;
+        let states = universe.rule.states();
+        for (idx, (&state, &color)) in snapshot.cells.iter().zip(&snapshot.colors).enumerate() {
+            let state = if state < states {
+                state
+            } else {
+                Cell::Dead as u8
+            };
+            universe.cells[idx] = state;
+            universe.colors[idx] = if state == Cell::Alive as u8 { color } else { 0 };
+        }
+        universe.reset_ages();
+        universe
+    }
+    fn resize(&mut self, width: u32, height: u32) -> Result<(), JsError> {
+        check_size(width, height)?;
+        self.record(Event::Resize(width, height));
+        let mut cells = vec![Cell::Dead as u8; (width * height) as usize];
+        let mut ages = vec![0; (width * height) as usize];
+        let mut quiet = vec![0; (width * height) as usize];
+        let mut colors = vec![0; (width * height) as usize];
+        for row in 0..height.min(self.height) {
+            for col in 0..width.min(self.width) {
+                let idx = self.get_index(row, col);
+                cells[(row * width + col) as usize] = self.cells[idx];
+                ages[(row * width + col) as usize] = self.ages[idx];
+                quiet[(row * width + col) as usize] = self.quiet[idx];
+                colors[(row * width + col) as usize] = self.colors[idx];
+            }
+        }
+        if let Some(heat) = self.heat.as_mut() {
+            let mut resized = vec![0; (width * height) as usize];
+            for row in 0..height.min(self.height) {
+                for col in 0..width.min(self.width) {
+                    resized[(row * width + col) as usize] = heat[(row * self.width + col) as usize];
+                }
+            }
+            *heat = resized;
+        }
+        self.width = width;
+        self.height = height;
+        self.tiles = Tiles::new(width, height);
+        self.cells = cells;
+        self.recount();
+        self.ages = ages;
+        self.quiet = quiet;
+        self.colors = colors;
+        self.thaw();
+        self.history.clear();
+        self.record_sync();
+        Ok(())
+    }
+    // Switching to a rule with fewer states drops cells whose state it no
+    // longer has.
+    fn apply_rule(&mut self, rule: Rule) {
+        self.record(Event::Rule(rule.to_string()));
+        self.rule = rule;
+        self.table = Table::new(&rule);
+        self.thaw();
+        self.history.clear();
+        let states = rule.states();
+        for (state, quiet) in self.cells.iter_mut().zip(self.quiet.iter_mut()) {
+            if *state >= states {
+                *state = Cell::Dead as u8;
+                *quiet = 0;
+            }
+        }
+        self.recount();
+        self.record_sync();
+    }
+    // Edits from before a tick can't be undone after it.
+    pub fn tick(&mut self) {
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.edits(&self.cells, &self.colors);
+            recorder.tick();
+        }
+        self.births = 0;
+        self.deaths = 0;
+        self.changed.clear();
+        if self.frozen {
+            return;
+        }
+        self.history.clear();
+        // cloned so marks can go in while parts of self are borrowed
+        let profiler = self.profiler.clone();
+        let profiling = profiler.as_ref();
+        mark(profiling, "tick:start");
+        // where applying the rule starts, after counting when that is apart
+        let mut applying = "tick:start";
+        // reuses the buffer the generation before last was in
+        let mut next = std::mem::take(&mut self.next);
+        next.clear();
+        next.extend_from_slice(&self.cells);
+        match self.rule {
+            Rule::LifeLike(rule) => {
+                let quiescent = rule.next_state(Cell::Dead as u8, 0) == Cell::Dead as u8;
+                let moore = self.neighborhood == Neighborhood::Moore && self.radius == 1;
+                let dense = self.dense(quiescent);
+                match &self.table {
+                    // with SIMD, counting 16 cells a step takes over from the table
+                    Some(table) if moore && dense && !counts::SIMD => {
+                        let padded = self.moore_counts.pad(
+                            &self.cells,
+                            self.width,
+                            self.height,
+                            self.boundary,
+                        );
+                        table.step(padded, self.width, self.height, &mut next);
+                    }
+                    _ if moore && dense => {
+                        let counts = self.moore_counts.count(
+                            &self.cells,
+                            self.width,
+                            self.height,
+                            self.boundary,
+                        );
+                        mark(profiling, "tick:counted");
+                        applying = "tick:counted";
+                        for ((next, &state), &count) in next.iter_mut().zip(&self.cells).zip(counts)
+                        {
+                            *next = rule.next_state(state, count as u32);
+                        }
+                    }
+                    _ => self.step_cells(&mut next, quiescent, false, |universe, row, col, idx| {
+                        let live_neighbors = universe.live_neighbor_count(row, col);
+                        rule.next_state(universe.cells[idx], live_neighbors)
+                    }),
+                }
+            }
+            Rule::Isotropic(rule) => {
+                let quiescent = rule.next_state(Cell::Dead as u8, 0) == Cell::Dead as u8;
+                let dense = self.dense(quiescent);
+                match &self.table {
+                    Some(table) if dense => {
+                        let padded = self.moore_counts.pad(
+                            &self.cells,
+                            self.width,
+                            self.height,
+                            self.boundary,
+                        );
+                        table.step(padded, self.width, self.height, &mut next);
+                    }
+                    _ => self.step_cells(&mut next, quiescent, true, |universe, row, col, idx| {
+                        let config = universe.neighbor_configuration(row, col);
+                        rule.next_state(universe.cells[idx], config)
+                    }),
+                }
+            }
+            Rule::LargerThanLife(rule) => {
+                let counts =
+                    rule.neighbor_counts(&self.cells, self.width, self.height, self.boundary);
+                mark(profiling, "tick:counted");
+                applying = "tick:counted";
+                for (idx, &live_neighbors) in counts.iter().enumerate() {
+                    next[idx] = rule.next_state(self.cells[idx], live_neighbors);
+                }
+            }
+            Rule::Majority => {
+                for row in 0..self.height {
+                    for col in 0..self.width {
+                        let idx = self.get_index(row, col);
+                        let (alive, total) = self.vote_count(row, col);
+                        next[idx] = majority_state(
+                            self.cells[idx],
+                            alive,
+                            total,
+                            self.tie_break,
+                            self.noise,
+                            &mut self.rng,
+                        );
+                    }
+                }
+            }
+        }
+        if self.birth_probability < 1.0 || self.survival_probability < 1.0 {
+            self.apply_probabilities(&mut next);
+        }
+        mark(profiling, "tick:applied");
+        if applying == "tick:counted" {
+            measure(profiling, "tick: count neighbors", "tick:start", applying);
+        }
+        measure(profiling, "tick: apply rule", applying, "tick:applied");
+        let colors = if self.color_variant != ColorVariant::Off {
+            self.next_colors(&next)
+        } else {
+            Vec::new()
+        };
+        self.status = match next == self.cells {
+            true if self.population == 0 => Status::Extinct,
+            true => Status::Still,
+            false => Status::Running,
+        };
+        if self.periods.is_empty() {
+            self.periods.push(period::hash(&self.cells));
+        }
+        self.periods.push(period::hash(&next));
+        if self.freeze_when_stable && (next == self.cells || next == self.previous) {
+            self.frozen = true;
+            self.next = next;
+            return;
+        }
+        self.record_tick(&next);
+        for (idx, (quiet, (&state, &next_state))) in self
+            .quiet
+            .iter_mut()
+            .zip(self.cells.iter().zip(&next))
+            .enumerate()
+        {
+            *quiet = if state == next_state {
+                quiet.saturating_add(1)
+            } else {
+                self.changed.push(idx as u32);
+                0
+            };
+        }
+        // chance can change a cell with nothing changing around it
+        let random = self.birth_probability < 1.0
+            || self.survival_probability < 1.0
+            || self.rule == Rule::Majority;
+        if random {
+            self.tiles.wake_all();
+        } else {
+            let radius = match self.rule {
+                Rule::Isotropic(_) => 1,
+                _ => self.radius,
+            };
+            self.tiles.settle(&self.changed, radius, self.boundary);
+        }
+        mark(profiling, "tick:tracked");
+        measure(
+            profiling,
+            "tick: track changes",
+            "tick:applied",
+            "tick:tracked",
+        );
+        if self.freeze_when_stable {
+            self.previous.clear();
+            self.previous.extend_from_slice(&self.cells);
+        }
+        // copied rather than swapped in, so `cells` stays where views of it
+        // in JS point
+        self.cells.copy_from_slice(&next);
+        self.next = next;
+        mark(profiling, "tick:swapped");
+        measure(
+            profiling,
+            "tick: swap buffers",
+            "tick:tracked",
+            "tick:swapped",
+        );
+        if self.color_variant != ColorVariant::Off {
+            self.colors = colors;
+        }
+        self.population = 0;
+        // only live cells have an age, so it still says which ones were alive
+        for (age, &state) in self.ages.iter_mut().zip(self.cells.iter()) {
+            let (was_alive, alive) = (*age > 0, state == Cell::Alive as u8);
+            self.births += (alive && !was_alive) as u32;
+            self.deaths += (was_alive && !alive) as u32;
+            *age = if alive {
+                self.population += 1;
+                age.saturating_add(1)
+            } else {
+                0
+            };
+        }
+        if self.population == 0 {
+            self.status = Status::Extinct;
+        }
+        if let Some(heat) = self.heat.as_mut() {
+            for (heat, &state) in heat.iter_mut().zip(self.cells.iter()) {
+                *heat += (state == Cell::Alive as u8) as u32;
+            }
+        }
+        self.generation += 1;
+        self.population_history.push(self.population);
+        self.drift.push(self.center());
+        self.record_sync();
+        self.autosave_if_due();
+        mark(profiling, "tick:end");
+        measure(profiling, "tick", "tick:start", "tick:end");
+    }
+    pub fn tick_n(&mut self, n: u32) {
+        for _ in 0..n {
+            self.tick();
+        }
+    }
+    // Runs `n` ticks and returns how long they took in microseconds, timed
+    // from inside wasm so the bindgen calls around it don't count. Browsers
+    // round performance.now(), so time enough ticks to be well above that.
+    pub fn tick_timed(&mut self, n: u32) -> Result<f64, JsError> {
+        let performance = performance()?;
+        let start = performance.now();
+        self.tick_n(n);
+        Ok((performance.now() - start) * 1000.0)
+    }
+    // Ticks until `budget` milliseconds have gone by and returns how many
+    // generations ran, for a render loop that steps as far as it can each
+    // frame. The last tick can run over, so leave room for one. Stops early
+    // once frozen, as further ticks would do nothing.
+    pub fn run_for_ms(&mut self, budget: f64) -> Result<u32, JsError> {
+        let performance = performance()?;
+        let deadline = performance.now() + budget;
+        let start = self.generation;
+        while !self.frozen && performance.now() < deadline {
+            self.tick();
+        }
+        Ok((self.generation - start) as u32)
+    }
+    // How many of the latest ticks to keep for rewind(), 0 to keep none.
+    pub fn rewind_limit(&self) -> u32 {
+        self.timeline.limit() as u32
+    }
+    pub fn set_rewind_limit(&mut self, limit: u32) {
+        self.timeline.set_limit(limit as usize);
+    }
+    pub fn rewindable(&self) -> u32 {
+        self.timeline.len() as u32
+    }
+    // Takes back up to `n` ticks, returning how many it could.
+    pub fn rewind(&mut self, n: u32) -> u32 {
+        let mut rewound = 0;
+        while rewound < n {
+            let changed = match self.timeline.pop() {
+                Some(changed) => changed,
+                None => break,
+            };
+            // cells the tick didn't change only got a generation older
+            for (age, &state) in self.ages.iter_mut().zip(self.cells.iter()) {
+                if state == Cell::Alive as u8 {
+                    *age = age.saturating_sub(1);
+                }
+            }
+            self.quiet
+                .iter_mut()
+                .for_each(|quiet| *quiet = quiet.saturating_sub(1));
+            for before in changed {
+                let idx = before.index as usize;
+                self.cells[idx] = before.state;
+                self.colors[idx] = before.color;
+                self.ages[idx] = before.age;
+                self.quiet[idx] = 0;
+            }
+            self.generation = self.generation.saturating_sub(1);
+            rewound += 1;
+        }
+        self.recount();
+        if rewound > 0 {
+            self.frozen = false;
+            self.status = Status::Running;
+            self.periods.clear();
+            self.previous.clear();
+            self.history.clear();
+            self.drift.clear();
+            self.tiles.wake_all();
+        }
+        rewound
     }
     pub fn new() -> Universe {
-        let width = 170 * 4;
-        let height = 70 * 4;
+        Universe::sized(170 * 4, 70 * 4)
+    }
+    pub fn new_with_size(width: u32, height: u32) -> Result<Universe, JsError> {
+        check_size(width, height)?;
+        Ok(Universe::sized(width, height))
+    }
+    // new_with_size() for a size already checked
+    fn sized(width: u32, height: u32) -> Universe {
         let cells = (0..width * height)
             .map(|i| {
                 if i % 2 == 0 || i % 7 == 0 || i % 11 == 0 {
-                    Cell::Alive
+                    Cell::Alive as u8
                 } else {
-                    Cell::Dead
+                    Cell::Dead as u8
                 }
             })
             .collect();
-        Universe {
+        let mut universe = Universe {
             width,
             height,
             cells,
+            ages: Vec::new(),
+            quiet: Vec::new(),
+            colors: vec![0; (width * height) as usize],
+            color_variant: ColorVariant::default(),
+            generation: 0,
+            boundary: Boundary::default(),
+            rule: Rule::default(),
+            neighborhood: Neighborhood::default(),
+            radius: 1,
+            neighbor_offsets: Neighborhood::default().offsets(1),
+            tie_break: TieBreak::default(),
+            noise: 0.0,
+            birth_probability: 1.0,
+            survival_probability: 1.0,
+            rng: Rng::new(0),
+            freeze_when_stable: false,
+            profiler: None,
+            frozen: false,
+            status: Status::default(),
+            periods: Periods::default(),
+            previous: Vec::new(),
+            next: Vec::new(),
+            view: (0, 0, 0),
+            view_version: 0,
+            rgba: Vec::new(),
+            active: Vec::new(),
+            marked: Vec::new(),
+            moore_counts: MooreCounts::default(),
+            table: Table::new(&Rule::default()),
+            tiles: Tiles::new(width, height),
+            population: 0,
+            births: 0,
+            deaths: 0,
+            changed: Vec::new(),
+            population_history: Series::default(),
+            drift: Drift::default(),
+            heat: None,
+            history: History::default(),
+            timeline: Timeline::default(),
+            autosave: None,
+            recorder: None,
+        };
+        universe.reset_ages();
+        universe
+    }
+    // Compact binary snapshot of the cells and colors together with the
+    // generation and the settings that decide how they evolve, for load().
+    pub fn save(&self) -> Vec<u8> {
+        self.saved().encode()
+    }
+    pub fn load(bytes: &[u8]) -> Result<Universe, JsError> {
+        Ok(Universe::restored(Snapshot::decode(bytes)?))
+    }
+    // save() as base64url text short enough for a link's URL fragment.
+    pub fn to_share_string(&self) -> String {
+        base64::encode(&self.save())
+    }
+    pub fn from_share_string(text: &str) -> Result<Universe, JsError> {
+        let bytes = base64::decode(text.trim().trim_start_matches('#'))
+            .ok_or_else(|| JsError::new("share string is not base64url"))?;
+        Universe::load(&bytes)
+    }
+    // Saves the universe to localStorage under `key` now, and from then on
+    // after ticks and edits whenever `interval` milliseconds have passed
+    // since the last save.
+    pub fn enable_autosave(&mut self, key: &str, interval: u32) -> Result<(), JsError> {
+        autosave::write(key, &self.to_share_string())?;
+        let mut autosave = Autosave::new(key, interval);
+        autosave.saved();
+        self.autosave = Some(autosave);
+        Ok(())
+    }
+    pub fn disable_autosave(&mut self) {
+        self.autosave = None;
+    }
+    pub fn autosave_key(&self) -> Option<String> {
+        self.autosave.as_ref().map(|autosave| autosave.key.clone())
+    }
+    // The universe last autosaved under `key`, if there is one. Autosave
+    // isn't turned back on for it.
+    pub fn restore_autosave(key: &str) -> Result<Option<Universe>, JsError> {
+        match autosave::read(key)? {
+            Some(text) => Ok(Some(Universe::from_share_string(&text)?)),
+            None => Ok(None),
         }
     }
+    pub fn discard_autosave(key: &str) -> Result<(), JsError> {
+        autosave::remove(key)
+    }
+    // Starts logging everything that changes the universe, from its state
+    // now, for replay(). Seeded fills and soups are logged as the cells they
+    // set, so only the rule's own randomness depends on the seed.
+    pub fn start_recording(&mut self) {
+        self.frozen = false;
+        self.previous.clear();
+        let start = Start {
+            snapshot: self.save(),
+            rng: self.rng.state(),
+            noise: self.noise,
+            birth_probability: self.birth_probability,
+            survival_probability: self.survival_probability,
+            freeze_when_stable: self.freeze_when_stable,
+        };
+        self.recorder = Some(Recorder::new(&start, &self.cells, &self.colors));
+    }
+    pub fn stop_recording(&mut self) {
+        self.recorder = None;
+    }
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+    // The log so far, while recording.
+    pub fn replay_log(&self) -> Result<Vec<u8>, JsError> {
+        match &self.recorder {
+            Some(recorder) => Ok(recorder.export(&self.cells, &self.colors)),
+            None => Err(JsError::new("not recording")),
+        }
+    }
+    // Plays a replay_log() back from its start, ending up exactly where the
+    // recorded universe was when the log was taken.
+    pub fn replay(log: &[u8]) -> Result<Universe, JsError> {
+        let (start, events) = replay::decode(log)?;
+        let mut universe = Universe::load(&start.snapshot)?;
+        universe.rng = Rng::new(start.rng);
+        universe.noise = start.noise;
+        universe.birth_probability = start.birth_probability;
+        universe.survival_probability = start.survival_probability;
+        universe.freeze_when_stable = start.freeze_when_stable;
+        for event in events {
+            match event {
+                Event::Ticks(n) => (0..n).for_each(|_| universe.tick()),
+                Event::Cells(cells) => {
+                    for (idx, state, color) in cells {
+                        if idx < universe.cells.len() {
+                            universe.write(idx, state);
+                            universe.colors[idx] = color;
+                        }
+                    }
+                }
+                Event::Rule(rule) => universe.set_rule(&rule)?,
+                Event::Neighborhood(neighborhood, radius) => {
+                    universe.set_neighborhood(neighborhood, radius)
+                }
+                Event::Boundary(boundary) => universe.set_boundary(boundary),
+                Event::TieBreak(tie_break) => universe.set_tie_break(tie_break),
+                Event::Noise(noise) => universe.set_noise(noise),
+                Event::BirthProbability(probability) => universe.set_birth_probability(probability),
+                Event::SurvivalProbability(probability) => {
+                    universe.set_survival_probability(probability)
+                }
+                Event::Reseed(seed) => universe.reseed(seed),
+                Event::Resize(width, height) => universe.resize(width, height)?,
+                Event::ColorVariant(variant, seed) => universe.set_color_variant(variant, seed),
+                Event::FreezeWhenStable(freeze) => universe.set_freeze_when_stable(freeze),
+            }
+        }
+        Ok(universe)
+    }
+    // Compact patch that apply_patch() turns this universe into `other` with,
+    // for keeping copies of a universe in step without sending all of it.
+    pub fn diff(&self, other: &Universe) -> Result<Vec<u8>, JsError> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return Err(SnapshotError::SizeMismatch.into());
+        }
+        let patch = Patch::between(
+            (self.width, self.height),
+            (&self.cells, &self.colors),
+            (&other.cells, &other.colors),
+            other.generation,
+        );
+        Ok(patch.encode())
+    }
+    pub fn apply_patch(&mut self, patch: &[u8]) -> Result<(), JsError> {
+        let patch = Patch::decode(patch)?;
+        if (self.width, self.height) != (patch.width, patch.height) {
+            return Err(SnapshotError::SizeMismatch.into());
+        }
+        for (idx, state, color) in patch.cells {
+            self.write(idx, state);
+            self.colors[idx] = color;
+        }
+        self.generation = patch.generation;
+        Ok(())
+    }
+    pub fn fit(canvas_width: u32, canvas_height: u32, cell_size: u32) -> Result<Universe, JsError> {
+        Universe::new_with_size(
+            cells_for_pixels(canvas_width, cell_size),
+            cells_for_pixels(canvas_height, cell_size),
+        )
+    }
+    pub fn fit_to_canvas(
+        &mut self,
+        canvas_width: u32,
+        canvas_height: u32,
+        cell_size: u32,
+    ) -> Result<(), JsError> {
+        self.resize(
+            cells_for_pixels(canvas_width, cell_size),
+            cells_for_pixels(canvas_height, cell_size),
+        )
+    }
     pub fn width(&self) -> u32 {
         self.width
     }
     pub fn height(&self) -> u32 {
         self.height
     }
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.record(Event::Boundary(boundary));
+        self.boundary = boundary;
+        self.periods.clear();
+        self.tiles.wake_all();
+    }
+    pub fn rule(&self) -> String {
+        self.rule.to_string()
+    }
+    pub fn set_rule(&mut self, rule: &str) -> Result<(), JsError> {
+        self.apply_rule(rule.parse()?);
+        Ok(())
+    }
+    pub fn set_rule_preset(&mut self, preset: RulePreset) {
+        self.apply_rule(preset.rule());
+    }
+    pub fn state_count(&self) -> u8 {
+        self.rule.states()
+    }
+    pub fn rule_preset_names() -> Vec<String> {
+        RulePreset::ALL
+            .iter()
+            .map(|preset| preset.name().to_string())
+            .collect()
+    }
+    pub fn rule_preset_rulestrings() -> Vec<String> {
+        RulePreset::ALL
+            .iter()
+            .map(|preset| preset.rulestring().to_string())
+            .collect()
+    }
+    pub fn neighborhood(&self) -> Neighborhood {
+        self.neighborhood
+    }
+    pub fn radius(&self) -> u32 {
+        self.radius
+    }
+    pub fn set_neighborhood(&mut self, neighborhood: Neighborhood, radius: u32) {
+        self.record(Event::Neighborhood(neighborhood, radius));
+        self.neighborhood = neighborhood;
+        self.radius = radius.clamp(1, MAX_RADIUS);
+        self.neighbor_offsets = neighborhood.offsets(self.radius);
+        self.periods.clear();
+        self.tiles.wake_all();
+    }
+    // Interleaved x, y render positions of every cell, in `cells()` order, for
+    // drawing a Hexagonal universe as hexagons of circumradius `size`.
+    pub fn hex_centers(&self, size: f64) -> Vec<f64> {
+        let mut centers = Vec::with_capacity(self.cells.len() * 2);
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let (x, y) = hex_center(row, col, self.height, size);
+                centers.push(x);
+                centers.push(y);
+            }
+        }
+        centers
+    }
+    pub fn tie_break(&self) -> TieBreak {
+        self.tie_break
+    }
+    pub fn set_tie_break(&mut self, tie_break: TieBreak) {
+        self.record(Event::TieBreak(tie_break));
+        self.tie_break = tie_break;
+    }
+    pub fn noise(&self) -> f32 {
+        self.noise
+    }
+    pub fn set_noise(&mut self, noise: f32) {
+        self.record(Event::Noise(noise));
+        self.noise = noise.clamp(0.0, 1.0);
+    }
+    pub fn birth_probability(&self) -> f32 {
+        self.birth_probability
+    }
+    pub fn set_birth_probability(&mut self, probability: f32) {
+        self.record(Event::BirthProbability(probability));
+        self.birth_probability = probability.clamp(0.0, 1.0);
+    }
+    pub fn survival_probability(&self) -> f32 {
+        self.survival_probability
+    }
+    pub fn set_survival_probability(&mut self, probability: f32) {
+        self.record(Event::SurvivalProbability(probability));
+        self.survival_probability = probability.clamp(0.0, 1.0);
+    }
+    // seeds the generator behind noise, random tie breaks and probabilities
+    pub fn reseed(&mut self, seed: u64) {
+        self.record(Event::Reseed(seed));
+        self.rng = Rng::new(seed);
+    }
+    // Stops the universe once it is still or only blinking with period 2,
+    // e.g. a finished maze.
+    pub fn set_freeze_when_stable(&mut self, freeze: bool) {
+        self.record(Event::FreezeWhenStable(freeze));
+        self.freeze_when_stable = freeze;
+        self.thaw();
+    }
+    pub fn freeze_when_stable(&self) -> bool {
+        self.freeze_when_stable
+    }
+    // While on, each tick leaves performance marks and measures of its
+    // phases (counting neighbors where that is a step of its own, applying
+    // the rule, tracking changes, swapping buffers) for the browser's
+    // performance panel. They pile up there until cleared with
+    // performance.clearMarks() and clearMeasures().
+    pub fn profiling(&self) -> bool {
+        self.profiler.is_some()
+    }
+    pub fn set_profiling(&mut self, profiling: bool) -> Result<(), JsError> {
+        self.profiler = if profiling {
+            Some(performance()?)
+        } else {
+            None
+        };
+        Ok(())
+    }
+    pub fn frozen(&self) -> bool {
+        self.frozen
+    }
+    pub fn status(&self) -> Status {
+        self.status
+    }
+    // 64-bit hash of the dimensions and every cell state, for telling states
+    // apart without copying the cells out. Colors and ages are left out.
+    pub fn state_hash(&self) -> u64 {
+        period::hash_sized(self.width, self.height, &self.cells)
+    }
+    // Period of the cycle the latest generations have settled into, 1 for a
+    // still life, up to 256. None until a generation repeats, and again
+    // after any edit.
+    pub fn detected_period(&self) -> Option<u32> {
+        self.periods.period()
+    }
+    pub fn set_width(&mut self, width: u32) -> Result<(), JsError> {
+        self.resize(width, self.height)
+    }
+    pub fn set_height(&mut self, height: u32) -> Result<(), JsError> {
+        self.resize(self.width, height)
+    }
+    // [min_row, min_column, max_row, max_column] of every non-dead cell, the
+    // same box exports are cropped to, or None for an empty universe.
+    pub fn bounding_box(&self) -> Option<Vec<u32>> {
+        let width = self.width as usize;
+        let occupied = |state: &u8| *state != Cell::Dead as u8;
+        let first = self.cells.iter().position(occupied)?;
+        let last = self.cells.iter().rposition(occupied)?;
+        let (mut left, mut right) = (width, 0);
+        for row in self.cells[first - first % width..=last].chunks(width) {
+            if let (Some(start), Some(end)) = (
+                row.iter().position(occupied),
+                row.iter().rposition(occupied),
+            ) {
+                left = left.min(start);
+                right = right.max(end);
+            }
+        }
+        Some(vec![
+            (first / width) as u32,
+            left as u32,
+            (last / width) as u32,
+            right as u32,
+        ])
+    }
+    // Number of separate clusters of live cells, joined through Moore
+    // neighbors for 8-connected clusters or VonNeumann ones for 4-connected.
+    pub fn label_components(&self, connectivity: Neighborhood) -> u32 {
+        self.components(connectivity).1
+    }
+    // The cluster of each cell in `cells()` order, numbered from 1 in scan
+    // order, with 0 for cells that aren't alive, e.g. to highlight the
+    // object under the cursor.
+    pub fn component_labels(&self, connectivity: Neighborhood) -> Vec<u32> {
+        self.components(connectivity).0
+    }
+    pub fn population(&self) -> u32 {
+        self.population
+    }
+    // Density, spatial entropy and cluster sizes of the current generation.
+    pub fn stats(&self) -> Stats {
+        stats::measure(
+            &self.cells,
+            self.width,
+            self.height,
+            self.boundary,
+            self.generation,
+            self.population,
+        )
+    }
+    // [row, column] of the live cells' center of mass, or None for an empty
+    // universe. On a torus it jumps when cells wrap around an edge.
+    pub fn center_of_mass(&self) -> Option<Vec<f64>> {
+        let (row, column) = self.center()?;
+        Some(vec![row, column])
+    }
+    // How many of the latest ticks drift_velocity() averages over, at least 2.
+    pub fn drift_window(&self) -> u32 {
+        self.drift.window() as u32
+    }
+    pub fn set_drift_window(&mut self, window: u32) {
+        self.drift.set_window(window as usize);
+    }
+    // [rows, columns] the center of mass moved per tick over the drift
+    // window, for following a spaceship. None until two ticks have run since
+    // the last edit or extinction.
+    pub fn drift_velocity(&self) -> Option<Vec<f64>> {
+        let (rows, columns) = self.drift.velocity()?;
+        Some(vec![rows, columns])
+    }
+    // How many ticks population_history() goes back, 0 to keep none.
+    pub fn population_history_limit(&self) -> u32 {
+        self.population_history.limit() as u32
+    }
+    pub fn set_population_history_limit(&mut self, limit: u32) {
+        self.population_history.set_limit(limit as usize);
+    }
+    // The population after each of the latest ticks, oldest first, ending
+    // with the current generation. Like cells(), the view is only valid until
+    // the next tick.
+    pub fn population_history(&self) -> js_sys::Uint32Array {
+        unsafe { js_sys::Uint32Array::view(self.population_history.values()) }
+    }
+    // Starts counting, on every tick, the generations each cell is alive for
+    // a long-exposure heatmap.
+    pub fn enable_heatmap(&mut self) {
+        self.heat = Some(vec![0; self.cells.len()]);
+    }
+    pub fn disable_heatmap(&mut self) {
+        self.heat = None;
+    }
+    pub fn clear_heatmap(&mut self) {
+        if let Some(heat) = self.heat.as_mut() {
+            heat.iter_mut().for_each(|heat| *heat = 0);
+        }
+    }
+    // The counts in `cells()` order, valid until the next tick, or None with
+    // the heatmap off.
+    pub fn heatmap(&self) -> Option<js_sys::Uint32Array> {
+        let heat = self.heat.as_ref()?;
+        Some(unsafe { js_sys::Uint32Array::view(heat) })
+    }
+    // The counts scaled so the hottest cell is 1.0.
+    pub fn heatmap_normalized(&self) -> Option<Vec<f32>> {
+        let heat = self.heat.as_ref()?;
+        let max = heat.iter().copied().max().unwrap_or(0).max(1) as f32;
+        Some(heat.iter().map(|&heat| heat as f32 / max).collect())
+    }
+    // Indices into cells() of every cell the last tick changed, in order, so
+    // a renderer can redraw just those. Edits and rewind() aren't included,
+    // so redraw everything after them. Valid until the next tick.
+    pub fn changed_cells(&self) -> js_sys::Uint32Array {
+        unsafe { js_sys::Uint32Array::view(&self.changed) }
+    }
+    pub fn births_last_tick(&self) -> u32 {
+        self.births
+    }
+    pub fn deaths_last_tick(&self) -> u32 {
+        self.deaths
+    }
+    pub fn get_cell(&self, row: u32, column: u32) -> Cell {
+        if self.get_state(row, column) == Cell::Alive as u8 {
+            Cell::Alive
+        } else {
+            Cell::Dead
+        }
+    }
+    pub fn set_cell(&mut self, row: u32, column: u32, cell: Cell) {
+        self.set_state(row, column, cell as u8);
+    }
+    // Off the grid every cell reads as dead, and edits are ignored.
+    pub fn get_state(&self, row: u32, column: u32) -> u8 {
+        self.checked_index(row, column)
+            .map_or(0, |idx| self.cells[idx])
+    }
+    pub fn set_state(&mut self, row: u32, column: u32, state: u8) {
+        if let Some(idx) = self.checked_index(row, column) {
+            let before = self.cells[idx];
+            self.write(idx, state);
+            self.history.record(idx, before, self.cells[idx]);
+            self.autosave_if_due();
+        }
+    }
+    pub fn toggle_cell(&mut self, row: u32, column: u32) {
+        let cell = match self.get_cell(row, column) {
+            Cell::Dead => Cell::Alive,
+            Cell::Alive => Cell::Dead,
+        };
+        self.set_cell(row, column, cell);
+    }
+    // Sets the cells at the given indices of cells() in one call from a
+    // Uint32Array, skipping indices past the end of the grid.
+    pub fn set_cells(&mut self, indices: &[u32], cell: Cell) {
+        let (len, width) = (self.cells.len() as u32, self.width);
+        self.edit(|universe| {
+            for &idx in indices.iter().filter(|&&idx| idx < len) {
+                universe.set_state(idx / width, idx % width, cell as u8);
+            }
+        })
+    }
+    // set_cells() for a flat array of (row, column) pairs.
+    pub fn set_cells_at(&mut self, coordinates: &[u32], cell: Cell) {
+        self.edit(|universe| {
+            for pair in coordinates.chunks_exact(2) {
+                if pair[0] < universe.height && pair[1] < universe.width {
+                    universe.set_state(pair[0], pair[1], cell as u8);
+                }
+            }
+        })
+    }
+    // Every edit between begin_edit() and end_edit(), such as a stroke of
+    // set_cell() calls, undoes as one step. Other edits undo one call at a
+    // time.
+    pub fn begin_edit(&mut self) {
+        self.history.begin();
+    }
+    pub fn end_edit(&mut self) {
+        self.history.end(&self.cells);
+        self.autosave_if_due();
+    }
+    // Reverts the last edit, returning false when there is none. Running,
+    // resizing or changing the rule starts a fresh history.
+    pub fn undo(&mut self) -> bool {
+        let states = self.history.undo();
+        self.restore(states)
+    }
+    pub fn redo(&mut self) -> bool {
+        let states = self.history.redo();
+        self.restore(states)
+    }
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+    pub fn clear(&mut self) {
+        self.fill(Cell::Dead);
+    }
+    pub fn fill(&mut self, cell: Cell) {
+        self.edit(|universe| {
+            universe.history.rewrite(&universe.cells);
+            universe.cells.iter_mut().for_each(|c| *c = cell as u8);
+            universe.reset_ages();
+            universe.reset_colors(None);
+            universe.generation = 0;
+            universe.thaw();
+        })
+    }
+    pub fn randomize(&mut self, density: f32, seed: u64) {
+        self.edit(|universe| {
+            universe.history.rewrite(&universe.cells);
+            let mut rng = Rng::new(seed);
+            for cell in universe.cells.iter_mut() {
+                *cell = if rng.next_f32() < density {
+                    Cell::Alive as u8
+                } else {
+                    Cell::Dead as u8
+                };
+            }
+            universe.reset_ages();
+            universe.reset_colors(Some(&mut rng));
+            universe.generation = 0;
+            universe.thaw();
+        })
+    }
+    // Clears the universe and randomizes only a centered `size` x `size`
+    // core, the usual start for growing a maze with the Maze or Mazectric
+    // presets.
+    pub fn seed_core(&mut self, size: u32, density: f32, seed: u64) {
+        self.edit(|universe| {
+            universe.fill(Cell::Dead);
+            let mut rng = Rng::new(seed);
+            let (rows, cols) = (size.min(universe.height), size.min(universe.width));
+            let (top, left) = ((universe.height - rows) / 2, (universe.width - cols) / 2);
+            for row in top..top + rows {
+                for col in left..left + cols {
+                    if rng.next_f32() < density {
+                        let idx = universe.get_index(row, col);
+                        universe.cells[idx] = Cell::Alive as u8;
+                    }
+                }
+            }
+            universe.reset_ages();
+            universe.reset_colors(Some(&mut rng));
+        })
+    }
+    // Clears the universe and fills a centered `size` x `size` square with a
+    // random soup of the given symmetry, as apgsearch does.
+    pub fn symmetric_soup(&mut self, size: u32, density: f32, symmetry: Symmetry, seed: u64) {
+        self.edit(|universe| {
+            universe.fill(Cell::Dead);
+            let mut rng = Rng::new(seed);
+            let size = size.min(universe.width).min(universe.height);
+            let (top, left) = ((universe.height - size) / 2, (universe.width - size) / 2);
+            for row in 0..size {
+                for col in 0..size {
+                    let orbit = symmetry.orbit(size, row, col);
+                    // each orbit is decided once, at its first cell in scan order
+                    if orbit[0] != (row, col) || rng.next_f32() >= density {
+                        continue;
+                    }
+                    for (orbit_row, orbit_col) in orbit {
+                        let idx = universe.get_index(top + orbit_row, left + orbit_col);
+                        universe.cells[idx] = Cell::Alive as u8;
+                    }
+                }
+            }
+            universe.reset_ages();
+            universe.reset_colors(Some(&mut rng));
+        })
+    }
+    // Runs a symmetric_soup() on a scratch copy of the universe, with the
+    // same size, rule and boundary, until its generations repeat or
+    // `max_generations` have passed, leaving this universe alone.
+    pub fn run_soup(
+        &self,
+        size: u32,
+        density: f32,
+        symmetry: Symmetry,
+        seed: u64,
+        max_generations: u64,
+    ) -> SoupResult {
+        let mut scratch = Universe::restored(self.saved());
+        scratch.symmetric_soup(size, density, symmetry, seed);
+        while scratch.periods.period().is_none() && scratch.generation < max_generations {
+            scratch.tick();
+        }
+        let period = scratch.periods.period();
+        SoupResult {
+            seed,
+            lifespan: scratch.generation - period.unwrap_or(0) as u64,
+            population: scratch.population,
+            period,
+        }
+    }
+    // run_soup() for `count` consecutive seeds from `first_seed`, e.g. to
+    // hunt for methuselahs.
+    pub fn run_soups(
+        &self,
+        size: u32,
+        density: f32,
+        symmetry: Symmetry,
+        first_seed: u64,
+        count: u32,
+        max_generations: u64,
+    ) -> Vec<SoupResult> {
+        (0..count as u64)
+            .map(|k| {
+                let seed = first_seed.wrapping_add(k);
+                self.run_soup(size, density, symmetry, seed, max_generations)
+            })
+            .collect()
+    }
+    pub fn color_variant(&self) -> ColorVariant {
+        self.color_variant
+    }
+    // Turning a variant on colors the live cells at random.
+    pub fn set_color_variant(&mut self, variant: ColorVariant, seed: u64) {
+        self.record(Event::ColorVariant(variant, seed));
+        self.color_variant = variant;
+        self.timeline.clear();
+        self.reset_colors(Some(&mut Rng::new(seed)));
+        self.record_sync();
+    }
+    // Sets every cell on the line between two cells, both included, so
+    // pointer samples can be joined into a stroke.
+    pub fn draw_line(&mut self, row0: u32, column0: u32, row1: u32, column1: u32, cell: Cell) {
+        self.edit(|universe| {
+            for (row, col) in draw::line(row0 as i64, column0 as i64, row1 as i64, column1 as i64) {
+                universe.place(row, col, cell as u8);
+            }
+        })
+    }
+    pub fn draw_rect(
+        &mut self,
+        row: u32,
+        column: u32,
+        width: u32,
+        height: u32,
+        cell: Cell,
+        filled: bool,
+    ) {
+        self.edit(|universe| {
+            for (row, col) in draw::rect(row as i64, column as i64, width, height, filled) {
+                universe.place(row, col, cell as u8);
+            }
+        })
+    }
+    // The ellipse inscribed in the given rectangle.
+    pub fn draw_ellipse(
+        &mut self,
+        row: u32,
+        column: u32,
+        width: u32,
+        height: u32,
+        cell: Cell,
+        filled: bool,
+    ) {
+        self.edit(|universe| {
+            for (row, col) in draw::ellipse(row as i64, column as i64, width, height, filled) {
+                universe.place(row, col, cell as u8);
+            }
+        })
+    }
+    // Paint bucket: sets the cell and every cell connected to it through
+    // cells of the same state, with `connectivity` Moore for 8-connected
+    // regions and VonNeumann for 4-connected ones.
+    pub fn flood_fill(&mut self, row: u32, column: u32, cell: Cell, connectivity: Neighborhood) {
+        // nothing to fill off the grid
+        let start = match self.checked_index(row, column) {
+            Some(start) => start,
+            None => return,
+        };
+        self.edit(|universe| {
+            let target = universe.cells[start];
+            if target == cell as u8 {
+                return;
+            }
+            let offsets = connectivity.offsets(1);
+            let mut pending = vec![start];
+            universe.set_state(row, column, cell as u8);
+            while let Some(idx) = pending.pop() {
+                let (row, col) = (idx as u32 / universe.width, idx as u32 % universe.width);
+                let neighbors: Vec<usize> = neighbor_indices(
+                    &offsets,
+                    universe.boundary,
+                    row,
+                    col,
+                    universe.width,
+                    universe.height,
+                )
+                .filter(|&neighbor| universe.cells[neighbor] == target)
+                .collect();
+                for neighbor in neighbors {
+                    let (row, col) = (
+                        neighbor as u32 / universe.width,
+                        neighbor as u32 % universe.width,
+                    );
+                    universe.set_state(row, col, cell as u8);
+                    pending.push(neighbor);
+                }
+            }
+        })
+    }
+    // Copies the cells of a rectangle, cut off where it leaves the grid.
+    pub fn copy_region(&self, row: u32, column: u32, width: u32, height: u32) -> Clip {
+        let height = height.min(self.height.saturating_sub(row));
+        let width = width.min(self.width.saturating_sub(column));
+        let mut pattern = Pattern {
+            width,
+            height,
+            cells: Vec::new(),
+        };
+        for clip_row in 0..height {
+            for clip_col in 0..width {
+                let state = self.cells[self.get_index(row + clip_row, column + clip_col)];
+                if state != Cell::Dead as u8 {
+                    pattern.cells.push((clip_row, clip_col, state));
+                }
+            }
+        }
+        Clip { pattern }
+    }
+    // copy_region() that also clears the rectangle
+    pub fn cut_region(&mut self, row: u32, column: u32, width: u32, height: u32) -> Clip {
+        self.edit(|universe| {
+            let clip = universe.copy_region(row, column, width, height);
+            for clip_row in 0..clip.height() {
+                for clip_col in 0..clip.width() {
+                    universe.set_state(row + clip_row, column + clip_col, Cell::Dead as u8);
+                }
+            }
+            clip
+        })
+    }
+    // Pastes the clip with its top-left corner at (row, column).
+    pub fn paste_clip(&mut self, clip: &Clip, row: u32, column: u32, mode: BlendMode) {
+        self.blend(&clip.pattern, row, column, mode);
+    }
+    // Rotates the `size` x `size` square at (row, column) clockwise in place,
+    // shrinking it to fit the grid.
+    pub fn rotate_region(&mut self, row: u32, column: u32, size: u32, quarter_turns: u32) {
+        let size = size
+            .min(self.height.saturating_sub(row))
+            .min(self.width.saturating_sub(column));
+        self.transform_region(row, column, size, size, Transform::rotation(quarter_turns));
+    }
+    pub fn flip_region(&mut self, row: u32, column: u32, width: u32, height: u32, axis: Axis) {
+        self.transform_region(row, column, width, height, axis.flip());
+    }
+    // Moves every cell `rows` down and `columns` right. On a torus cells
+    // pushed past an edge come back on the other side; with any other
+    // boundary they are lost.
+    pub fn shift(&mut self, rows: i32, columns: i32) {
+        self.edit(|universe| {
+            let wraps = universe.boundary == Boundary::Wrap;
+            let target = |coord: u32, delta: i32, size: u32| {
+                let coord = coord as i64 + delta as i64;
+                if wraps {
+                    Boundary::Wrap.resolve(coord, size)
+                } else {
+                    Boundary::Dead.resolve(coord, size)
+                }
+            };
+            universe.history.rewrite(&universe.cells);
+            let mut cells = vec![Cell::Dead as u8; universe.cells.len()];
+            let mut ages = vec![0; universe.ages.len()];
+            let mut quiet = vec![0; universe.quiet.len()];
+            let mut colors = vec![0; universe.colors.len()];
+            for row in 0..universe.height {
+                for col in 0..universe.width {
+                    let (target_row, target_col) = match (
+                        target(row, rows, universe.height),
+                        target(col, columns, universe.width),
+                    ) {
+                        (Some(row), Some(col)) => (row, col),
+                        _ => continue,
+                    };
+                    let (idx, target_idx) = (
+                        universe.get_index(row, col),
+                        universe.get_index(target_row, target_col),
+                    );
+                    cells[target_idx] = universe.cells[idx];
+                    ages[target_idx] = universe.ages[idx];
+                    quiet[target_idx] = universe.quiet[idx];
+                    colors[target_idx] = universe.colors[idx];
+                }
+            }
+            universe.cells = cells;
+            universe.recount();
+            universe.ages = ages;
+            universe.quiet = quiet;
+            universe.colors = colors;
+            universe.thaw();
+        })
+    }
+    pub fn pattern_names() -> Vec<String> {
+        pattern::builtin_names().map(str::to_string).collect()
+    }
+    // Built-in patterns whose name, category or description contains
+    // `query`, or all of them for an empty query.
+    pub fn pattern_catalogue(query: &str) -> Vec<PatternInfo> {
+        pattern::catalogue(query)
+    }
+    // Places a built-in pattern such as "glider" or "gosper-glider-gun",
+    // transformed, with its top-left corner at (row, column).
+    pub fn insert_pattern(
+        &mut self,
+        name: &str,
+        row: u32,
+        column: u32,
+        transform: Transform,
+        mode: BlendMode,
+    ) -> Result<(), JsError> {
+        let pattern = pattern::builtin(name)?.transformed(transform);
+        self.blend(&pattern, row, column, mode);
+        Ok(())
+    }
+    // Stamps a transformed RLE pattern with its top-left corner at (row,
+    // column). States the current rule doesn't have are pasted as dead cells.
+    pub fn paste_rle(
+        &mut self,
+        rle: &str,
+        row: u32,
+        column: u32,
+        transform: Transform,
+        mode: BlendMode,
+    ) -> Result<(), JsError> {
+        let pattern = pattern::parse_rle(rle, self.cells.len())?.transformed(transform);
+        self.blend(&pattern, row, column, mode);
+        Ok(())
+    }
+    // RLE of every non-dead cell, cropped to their bounding box, that Golly
+    // can open as is.
+    pub fn to_rle(&self) -> String {
+        pattern::format_rle(
+            &self.snapshot(),
+            &self.rule.to_string(),
+            self.rule.states() > 2,
+        )
+    }
+    // Stamps a transformed plaintext .cells pattern with its top-left corner
+    // at (row, column).
+    pub fn paste_cells(
+        &mut self,
+        text: &str,
+        row: u32,
+        column: u32,
+        transform: Transform,
+        mode: BlendMode,
+    ) -> Result<(), JsError> {
+        let pattern = pattern::parse_plaintext(text)?.transformed(transform);
+        self.blend(&pattern, row, column, mode);
+        Ok(())
+    }
+    // Stamps text in any format the crate reads, telling them apart by
+    // content, with its top-left corner at (row, column).
+    pub fn paste_text(
+        &mut self,
+        text: &str,
+        row: u32,
+        column: u32,
+        transform: Transform,
+        mode: BlendMode,
+    ) -> Result<(), JsError> {
+        let pattern = pattern::parse_any(text, self.cells.len())?.transformed(transform);
+        self.blend(&pattern, row, column, mode);
+        Ok(())
+    }
+    // Promise of the clipboard text, for paste_text(). This stands in for a
+    // paste_from_clipboard(row, column): reading the clipboard is
+    // asynchronous, and an exported async method can't hold `&mut self`
+    // across the await, as its future has to be 'static. So pasting takes
+    // one line of JS:
+    //
+    //     universe.paste_text(await Universe.clipboard_text(), row, column, transform, mode);
+    pub fn clipboard_text() -> Result<js_sys::Promise, JsError> {
+        Ok(clipboard()?.read_text())
+    }
+    // Writes the rectangle at (row, column) to the clipboard as RLE that
+    // Golly and LifeViewer can paste, returning the clipboard's promise.
+    pub fn copy_selection_to_clipboard(
+        &self,
+        row: u32,
+        column: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<js_sys::Promise, JsError> {
+        let clip = self.copy_region(row, column, width, height);
+        let rle = pattern::format_rle(
+            &clip.pattern,
+            &self.rule.to_string(),
+            self.rule.states() > 2,
+        );
+        Ok(clipboard()?.write_text(&rle))
+    }
+    // apgcode such as "xs4_33" or "xp2_7" naming the live cells as one
+    // object, found by running them on an unbounded plane until they repeat
+    // within `max_period` generations. The rule has to be a two-state rule
+    // on the radius 1 Moore neighborhood.
+    pub fn apgcode(&self, max_period: u32) -> Result<String, JsError> {
+        let rule = self.plane_rule()?;
+        let cells = apgcode::live_cells(&self.snapshot());
+        Ok(apgcode::identify(&rule, &cells, max_period)?)
+    }
+    // Tallies the objects on the grid by apgcode, most common first, as
+    // apgsearch does for a settled soup. Live cells within two cells of each
+    // other count as one object, identified the way apgcode() is, so this
+    // needs the same rules.
+    pub fn census(&self, max_period: u32) -> Result<Vec<CensusEntry>, JsError> {
+        let rule = self.plane_rule()?;
+        Ok(census::census(
+            &rule,
+            &self.cells,
+            self.width,
+            self.height,
+            max_period,
+        ))
+    }
+    // Places `count` copies of a built-in spaceship, transformed, in a stream
+    // along its direction of travel. The lead ship is `phase` generations on
+    // from the built-in with its top-left corner at (row, column), and every
+    // other ship trails the one ahead of it by `spacing` generations. Like
+    // apgcode(), this needs a two-state radius 1 Moore rule.
+    #[allow(clippy::too_many_arguments)]
+    pub fn place_stream(
+        &mut self,
+        name: &str,
+        row: u32,
+        column: u32,
+        transform: Transform,
+        count: u32,
+        spacing: u32,
+        phase: u32,
+    ) -> Result<(), JsError> {
+        let rule = self.plane_rule()?;
+        let ship = pattern::builtin(name)?.transformed(transform);
+        let motion = plane::find_motion(&rule, &apgcode::live_cells(&ship), 256)?;
+        let period = motion.period as i64;
+        // the ship `time` generations after the built-in, for any time
+        let at = |time: i64| {
+            let (laps, phase) = (time.div_euclid(period), time.rem_euclid(period));
+            let (delta_row, delta_col) = motion.displacement;
+            motion.phases[phase as usize]
+                .iter()
+                .map(move |&(r, c)| (r + laps * delta_row, c + laps * delta_col))
+        };
+        let lead: Vec<(i64, i64)> = at(phase as i64).collect();
+        let (top, left) = plane::origin(&lead);
+        let (offset_row, offset_col) = (row as i64 - top, column as i64 - left);
+        self.edit(|universe| {
+            for k in 0..count as i64 {
+                for (r, c) in at(phase as i64 - k * spacing as i64) {
+                    universe.place(offset_row + r, offset_col + c, Cell::Alive as u8);
+                }
+            }
+        });
+        Ok(())
+    }
+    // Stamps the first phase of an apgcode, transformed, with its top-left
+    // corner at (row, column).
+    pub fn paste_apgcode(
+        &mut self,
+        code: &str,
+        row: u32,
+        column: u32,
+        transform: Transform,
+        mode: BlendMode,
+    ) -> Result<(), JsError> {
+        let pattern = apgcode::decode(code)?.transformed(transform);
+        self.blend(&pattern, row, column, mode);
+        Ok(())
+    }
+    // Golly macrocell of every non-dead cell, cropped to their bounding box.
+    pub fn to_macrocell(&self) -> String {
+        macrocell::format_macrocell(
+            &self.snapshot(),
+            &self.rule.to_string(),
+            self.rule.states() > 2,
+        )
+    }
+    // Clears the universe and centers a macrocell pattern on it. Patterns
+    // with more live cells than the universe has cells are rejected.
+    pub fn load_macrocell(&mut self, text: &str) -> Result<(), JsError> {
+        let pattern = macrocell::parse_macrocell(text, self.cells.len())?;
+        self.load_pattern(&pattern);
+        Ok(())
+    }
+    // Clears the universe and centers a Life 1.06 pattern on it.
+    pub fn load_life106(&mut self, text: &str) -> Result<(), JsError> {
+        let pattern = pattern::parse_life106(text)?;
+        self.load_pattern(&pattern);
+        Ok(())
+    }
+    // Object URL of a file holding the universe in `format`, for the href
+    // of a download link.
+    pub fn export_url(&self, format: FileFormat) -> Result<String, JsError> {
+        let contents = match format {
+            FileFormat::Rle => self.to_rle().into_bytes(),
+            FileFormat::Macrocell => self.to_macrocell().into_bytes(),
+            FileFormat::Snapshot => self.save(),
+        };
+        files::object_url(&contents, format).map_err(|_| JsError::new("couldn't create the file"))
+    }
+    // Loads an uploaded file: a save() snapshot replaces the universe, and a
+    // pattern in any format paste_text() reads is centered on it.
+    pub fn import_file(&mut self, buffer: &js_sys::ArrayBuffer) -> Result<(), JsError> {
+        self.import_bytes(&js_sys::Uint8Array::new(buffer).to_vec())
+    }
+    // 0 for coordinates off the grid
+    pub fn get_color(&self, row: u32, column: u32) -> u8 {
+        self.checked_index(row, column)
+            .map_or(0, |idx| self.colors[idx])
+    }
+    // Recolors a live cell; colors outside 1..=colors and cells off the grid
+    // are ignored.
+    pub fn set_color(&mut self, row: u32, column: u32, color: u8) {
+        if let Some(idx) = self.checked_index(row, column) {
+            if self.cells[idx] == Cell::Alive as u8
+                && (1..=self.color_variant.colors()).contains(&color)
+            {
+                self.colors[idx] = color;
+            }
+        }
+    }
+    // colors in `cells()` order
+    pub fn colors(&self) -> js_sys::Uint8Array {
+        unsafe { js_sys::Uint8Array::view(&self.colors) }
+    }
+    // 0 for coordinates off the grid
+    pub fn get_age(&self, row: u32, column: u32) -> u16 {
+        self.checked_index(row, column)
+            .map_or(0, |idx| self.ages[idx])
+    }
+    // ages in `cells()` order, saturating at u16::MAX
+    pub fn ages(&self) -> js_sys::Uint16Array {
+        unsafe { js_sys::Uint16Array::view(&self.ages) }
+    }
+    // 0 for coordinates off the grid
+    pub fn get_generations_since_change(&self, row: u32, column: u32) -> u16 {
+        self.checked_index(row, column)
+            .map_or(0, |idx| self.quiet[idx])
+    }
+    // Generations since each cell last changed state, in `cells()` order and
+    // saturating at u16::MAX, for fading out quiet regions. A bulk edit such
+    // as fill() or randomize() counts as changing every cell.
+    pub fn generations_since_change(&self) -> js_sys::Uint16Array {
+        unsafe { js_sys::Uint16Array::view(&self.quiet) }
+    }
+    // Where the cells start in wasm memory, for building the view as
+    // `new Uint8Array(Universe.memory().buffer, cells_ptr(), cells_len())`.
+    // Ticks leave the cells where they are, but resizing or restoring can
+    // move them, and growing memory detaches every view, so rebuild it
+    // whenever view_version() changes.
+    pub fn cells_ptr(&self) -> *const u8 {
+        self.cells.as_ptr()
+    }
+    pub fn cells_len(&self) -> u32 {
+        self.cells.len() as u32
+    }
+    // The module's WebAssembly.Memory.
+    pub fn memory() -> JsValue {
+        wasm_bindgen::memory()
+    }
+    // Goes up each time the cells move or wasm memory grows, that is each
+    // time a view of the cells has to be made again. Cheap enough to check
+    // every frame.
+    pub fn view_version(&mut self) -> u32 {
+        let view = (
+            self.cells.as_ptr() as usize,
+            self.cells.len(),
+            memory_pages(),
+        );
+        if view != self.view {
+            self.view = view;
+            self.view_version += 1;
+        }
+        self.view_version
+    }
     pub fn cells(&self) -> js_sys::Uint8Array {
-        unsafe {
-            let u8_cells = mem::transmute::<&Vec<Cell>, &Vec<u8>>(&self.cells);
-            js_sys::Uint8Array::view(&u8_cells)
+        unsafe { js_sys::Uint8Array::view(&self.cells) }
+    }
+    // The grid as text, a line per row with ◼ for live cells and ◻ for the
+    // rest.
+    pub fn render(&self) -> String {
+        self.to_string()
+    }
+    // Draws every cell onto a 2D canvas context as `cell_size` squares with
+    // room for 1px grid lines between them, in `colors` indexed by cell
+    // state: dead, alive, then any dying states.
+    pub fn draw(
+        &self,
+        context: &web_sys::CanvasRenderingContext2d,
+        cell_size: u32,
+        colors: Vec<String>,
+    ) -> Result<(), JsError> {
+        renderer::draw(context, &self.cells, self.width, cell_size, &colors)
+    }
+    // Fills the RGBA buffer with the cells as `scale` x `scale` pixel blocks
+    // in `colors` (0xRRGGBBAA) indexed by cell state, for showing with one
+    // putImageData() as `new ImageData(new Uint8ClampedArray(
+    // Universe.memory().buffer, rgba_ptr(), rgba_len()), width() * scale)`.
+    // The buffer moves when it has to grow, so make the view again after
+    // each call.
+    pub fn render_rgba(&mut self, scale: u32, colors: &[u32]) -> Result<(), JsError> {
+        renderer::rgba(
+            &mut self.rgba,
+            &self.cells,
+            self.width,
+            scale.max(1),
+            colors,
+        )
+    }
+    pub fn rgba_ptr(&self) -> *const u8 {
+        self.rgba.as_ptr()
+    }
+    pub fn rgba_len(&self) -> u32 {
+        self.rgba.len() as u32
+    }
+}
+impl Default for Universe {
+    fn default() -> Self {
+        Universe::new()
+    }
+}
+impl fmt::Display for Universe {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.width == 0 {
+            return Ok(());
+        }
+        for line in self.cells.chunks(self.width as usize) {
+            for &state in line {
+                let symbol = if state == Cell::Alive as u8 {
+                    '◼'
+                } else {
+                    '◻'
+                };
+                write!(f, "{}", symbol)?;
+            }
+            writeln!(f)?;
         }
+        Ok(())
     }
 }
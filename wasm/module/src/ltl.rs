@@ -0,0 +1,182 @@
+use crate::neighborhood::MAX_RADIUS;
+use crate::rule::{next_state, parse_states, RuleError};
+use crate::{Boundary, Cell, Neighborhood};
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+// Larger than Life rule in Golly's "R5,C0,M1,S34..58,B34..45,NM" notation:
+// neighborhood radius, state count (0 meaning 2), whether the middle cell
+// counts towards its own sum, and the survival and birth intervals.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LtlRule {
+    radius: u32,
+    states: u8,
+    middle: bool,
+    survival: (u32, u32),
+    birth: (u32, u32),
+    neighborhood: Neighborhood,
+}
+impl LtlRule {
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+    pub fn next_state(&self, state: u8, live_neighbors: u32) -> u8 {
+        let within = |(min, max): (u32, u32)| (min..=max).contains(&live_neighbors);
+        next_state(
+            state,
+            within(self.birth),
+            within(self.survival),
+            self.states,
+        )
+    }
+    // Live cell count in every cell's neighborhood, middle included when the
+    // rule asks for it. Moore neighborhoods are read from a summed-area table
+    // so the cost per cell doesn't depend on the radius.
+    pub(crate) fn neighbor_counts(
+        &self,
+        cells: &[u8],
+        width: u32,
+        height: u32,
+        boundary: Boundary,
+    ) -> Vec<u32> {
+        let alive = |row: i64, col: i64| -> u32 {
+            match (boundary.resolve(row, height), boundary.resolve(col, width)) {
+                (Some(row), Some(col)) => {
+                    (cells[(row * width + col) as usize] == Cell::Alive as u8) as u32
+                }
+                _ => 0,
+            }
+        };
+        let r = self.radius as i64;
+        let mut counts = Vec::with_capacity(cells.len());
+        if self.neighborhood == Neighborhood::Moore {
+            let padded_width = width as usize + 2 * r as usize;
+            let padded_height = height as usize + 2 * r as usize;
+            let stride = padded_width + 1;
+            let mut table = vec![0u32; stride * (padded_height + 1)];
+            for y in 0..padded_height {
+                let mut row_sum = 0;
+                for x in 0..padded_width {
+                    row_sum += alive(y as i64 - r, x as i64 - r);
+                    table[(y + 1) * stride + x + 1] = table[y * stride + x + 1] + row_sum;
+                }
+            }
+            let side = 2 * r as usize + 1;
+            for row in 0..height as usize {
+                for col in 0..width as usize {
+                    let (top, left) = (row, col);
+                    let (bottom, right) = (row + side, col + side);
+                    counts.push(
+                        table[bottom * stride + right] + table[top * stride + left]
+                            - table[top * stride + right]
+                            - table[bottom * stride + left],
+                    );
+                }
+            }
+        } else {
+            let offsets = self.neighborhood.offsets(self.radius);
+            for row in 0..height as i64 {
+                for col in 0..width as i64 {
+                    let sum = offsets
+                        .iter()
+                        .map(|&(delta_row, delta_col)| alive(row + delta_row, col + delta_col))
+                        .sum::<u32>();
+                    counts.push(sum + alive(row, col));
+                }
+            }
+        }
+        if !self.middle {
+            for (count, &state) in counts.iter_mut().zip(cells) {
+                *count -= (state == Cell::Alive as u8) as u32;
+            }
+        }
+        counts
+    }
+}
+
+fn parse_range(range: &str) -> Result<(u32, u32), RuleError> {
+    let invalid = || RuleError::InvalidRange(range.to_string());
+    let (min, max) = range.split_once("..").ok_or_else(invalid)?;
+    let min = min.parse().map_err(|_| invalid())?;
+    let max = max.parse().map_err(|_| invalid())?;
+    Ok((min, max))
+}
+impl FromStr for LtlRule {
+    type Err = RuleError;
+    fn from_str(rule: &str) -> Result<LtlRule, RuleError> {
+        let malformed = || RuleError::Malformed(rule.to_string());
+        let mut radius = None;
+        let mut states = 2;
+        let mut middle = false;
+        let mut survival = None;
+        let mut birth = None;
+        let mut neighborhood = Neighborhood::Moore;
+        for part in rule.trim().split(',') {
+            let part = part.trim();
+            let mut chars = part.chars();
+            let key = chars.next().map(|c| c.to_ascii_uppercase());
+            let value = chars.as_str();
+            match key {
+                Some('R') => {
+                    const RADII: RangeInclusive<u32> = 1..=MAX_RADIUS;
+                    radius = match value.parse() {
+                        Ok(r) if RADII.contains(&r) => Some(r),
+                        _ => return Err(RuleError::InvalidRadius(value.to_string())),
+                    }
+                }
+                Some('C') => states = parse_states(if value == "0" { "2" } else { value })?,
+                Some('M') => {
+                    middle = match value {
+                        "0" => false,
+                        "1" => true,
+                        _ => return Err(malformed()),
+                    }
+                }
+                Some('S') => survival = Some(parse_range(value)?),
+                Some('B') => birth = Some(parse_range(value)?),
+                Some('N') => {
+                    neighborhood = match value.to_ascii_uppercase().as_str() {
+                        "M" => Neighborhood::Moore,
+                        "N" => Neighborhood::VonNeumann,
+                        "H" => Neighborhood::Hexagonal,
+                        _ => return Err(malformed()),
+                    }
+                }
+                _ => return Err(malformed()),
+            }
+        }
+        match (radius, survival, birth) {
+            (Some(radius), Some(survival), Some(birth)) => Ok(LtlRule {
+                radius,
+                states,
+                middle,
+                survival,
+                birth,
+                neighborhood,
+            }),
+            _ => Err(malformed()),
+        }
+    }
+}
+impl fmt::Display for LtlRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let neighborhood = match self.neighborhood {
+            Neighborhood::Moore => 'M',
+            Neighborhood::VonNeumann => 'N',
+            Neighborhood::Hexagonal => 'H',
+        };
+        write!(
+            f,
+            "R{},C{},M{},S{}..{},B{}..{},N{}",
+            self.radius,
+            if self.states == 2 { 0 } else { self.states },
+            self.middle as u8,
+            self.survival.0,
+            self.survival.1,
+            self.birth.0,
+            self.birth.1,
+            neighborhood
+        )
+    }
+}
@@ -0,0 +1,233 @@
+use crate::pattern::{Pattern, PatternError};
+use std::collections::HashMap;
+
+// Golly's macrocell format stores a pattern as a quadtree with identical
+// subtrees written once. Every line after the "[M2]" header is a node,
+// numbered from 1, and node 0 is the empty node of any size:
+//   - a two-state 8x8 leaf: rows of '.' and '*' each ended by '$', with
+//     trailing dead cells and rows left out
+//   - a multi-state level 1 node: "1 nw ne sw se" with the four states
+//   - a level n node: "n nw ne sw se" with the numbers of its four quadrants
+enum Node {
+    Leaf([u8; 64]),
+    Inner(u32, [usize; 4]),
+}
+impl Node {
+    fn level(&self) -> u32 {
+        match self {
+            Node::Leaf(_) => 3,
+            Node::Inner(level, _) => *level,
+        }
+    }
+}
+
+fn parse_leaf(line: &str) -> Result<[u8; 64], String> {
+    let mut cells = [0; 64];
+    let (mut row, mut col) = (0, 0);
+    for c in line.chars() {
+        match c {
+            '$' => {
+                row += 1;
+                col = 0;
+            }
+            '.' | '*' if row < 8 && col < 8 => {
+                cells[row * 8 + col] = (c == '*') as u8;
+                col += 1;
+            }
+            '.' | '*' => return Err(format!("leaf {:?} is larger than 8x8", line)),
+            _ => return Err(format!("unexpected {:?} in leaf", c)),
+        }
+    }
+    Ok(cells)
+}
+fn parse_inner(line: &str, nodes: &[Node]) -> Result<Node, String> {
+    let fields = line
+        .split_whitespace()
+        .map(|field| field.parse::<usize>())
+        .collect::<Result<Vec<usize>, _>>()
+        .map_err(|_| format!("malformed node {:?}", line))?;
+    if fields.len() != 5 {
+        return Err(format!("malformed node {:?}", line));
+    }
+    let level = fields[0] as u32;
+    let children = [fields[1], fields[2], fields[3], fields[4]];
+    if level == 1 {
+        if children.iter().any(|&state| state > 255) {
+            return Err(format!("state above 255 in {:?}", line));
+        }
+        return Ok(Node::Inner(1, children));
+    }
+    // ample for any universe while keeping every offset inside an i64
+    if !(2..=62).contains(&level) {
+        return Err(format!("unsupported level {}", level));
+    }
+    for &child in children.iter().filter(|&&child| child != 0) {
+        match nodes.get(child - 1) {
+            Some(node) if node.level() == level - 1 => {}
+            Some(_) => return Err(format!("node {} has the wrong level", child)),
+            None => return Err(format!("node {} is used before it is defined", child)),
+        }
+    }
+    Ok(Node::Inner(level, children))
+}
+// Number of live cells under `node`, given those of every earlier node.
+// Saturates, since a deep chain can double it at every level.
+fn population(node: &Node, populations: &[u64]) -> u64 {
+    match node {
+        Node::Leaf(leaf) => leaf.iter().filter(|&&state| state != 0).count() as u64,
+        Node::Inner(1, states) => states.iter().filter(|&&state| state != 0).count() as u64,
+        Node::Inner(_, children) => children
+            .iter()
+            .filter(|&&child| child != 0)
+            .fold(0u64, |sum, &child| {
+                sum.saturating_add(populations[child - 1])
+            }),
+    }
+}
+// Collects the live cells of node `index` placed at (top, left). Empty
+// subtrees are skipped, so the work stays proportional to the cells found.
+fn rasterize(
+    nodes: &[Node],
+    populations: &[u64],
+    index: usize,
+    top: i64,
+    left: i64,
+    cells: &mut Vec<(i64, i64, u8)>,
+) {
+    if index == 0 || populations[index - 1] == 0 {
+        return;
+    }
+    match &nodes[index - 1] {
+        Node::Leaf(leaf) => {
+            for (i, &state) in leaf.iter().enumerate().filter(|&(_, &state)| state != 0) {
+                cells.push((top + i as i64 / 8, left + i as i64 % 8, state));
+            }
+        }
+        Node::Inner(1, states) => {
+            for (i, &state) in states.iter().enumerate().filter(|&(_, &state)| state != 0) {
+                cells.push((top + i as i64 / 2, left + i as i64 % 2, state as u8));
+            }
+        }
+        Node::Inner(level, children) => {
+            let half = 1i64 << (level - 1);
+            for (i, &child) in children.iter().enumerate() {
+                let (row, col) = (top + half * (i as i64 / 2), left + half * (i as i64 % 2));
+                rasterize(nodes, populations, child, row, col, cells);
+            }
+        }
+    }
+}
+pub(crate) fn parse_macrocell(text: &str, limit: usize) -> Result<Pattern, PatternError> {
+    let mut lines = text.lines().map(str::trim);
+    if !lines
+        .next()
+        .is_some_and(|header| header.starts_with("[M2]"))
+    {
+        return Err(PatternError::InvalidMacrocell(
+            "missing [M2] header".to_string(),
+        ));
+    }
+    let (mut nodes, mut populations) = (Vec::new(), Vec::new());
+    for line in lines.filter(|line| !line.is_empty() && !line.starts_with('#')) {
+        let node = if line.starts_with(['.', '*', '$']) {
+            parse_leaf(line).map(Node::Leaf)
+        } else {
+            parse_inner(line, &nodes)
+        };
+        let node = node.map_err(PatternError::InvalidMacrocell)?;
+        populations.push(population(&node, &populations));
+        nodes.push(node);
+    }
+    // checked before rasterizing, which then visits at most 62 nodes per cell
+    if populations
+        .last()
+        .is_some_and(|&total| total > limit as u64)
+    {
+        return Err(PatternError::InvalidMacrocell(format!(
+            "more than {} live cells",
+            limit
+        )));
+    }
+    let mut cells = Vec::new();
+    rasterize(&nodes, &populations, nodes.len(), 0, 0, &mut cells);
+    Ok(Pattern::from_coordinates(&cells))
+}
+
+struct Writer {
+    grid: HashMap<(u32, u32), u8>,
+    multistate: bool,
+    lines: Vec<String>,
+    // line number of every node written so far
+    seen: HashMap<String, usize>,
+}
+impl Writer {
+    fn node(&mut self, line: String) -> usize {
+        if let Some(&index) = self.seen.get(&line) {
+            return index;
+        }
+        self.lines.push(line.clone());
+        self.seen.insert(line, self.lines.len());
+        self.lines.len()
+    }
+    fn state(&self, row: u32, col: u32) -> u8 {
+        self.grid.get(&(row, col)).copied().unwrap_or(0)
+    }
+    fn build(&mut self, level: u32, top: u32, left: u32) -> usize {
+        if !self.multistate && level == 3 {
+            let mut leaf = String::new();
+            for row in top..top + 8 {
+                let cells: String = (left..left + 8)
+                    .map(|col| if self.state(row, col) != 0 { '*' } else { '.' })
+                    .collect();
+                leaf.push_str(cells.trim_end_matches('.'));
+                leaf.push('$');
+            }
+            let leaf = leaf.trim_end_matches('$').to_string() + "$";
+            return if leaf == "$" { 0 } else { self.node(leaf) };
+        }
+        let children: Vec<usize> = if level == 1 {
+            (0..4)
+                .map(|i| self.state(top + i / 2, left + i % 2) as usize)
+                .collect()
+        } else {
+            let half = 1 << (level - 1);
+            (0..4)
+                .map(|i| self.build(level - 1, top + half * (i / 2), left + half * (i % 2)))
+                .collect()
+        };
+        if children.iter().all(|&child| child == 0) {
+            return 0;
+        }
+        self.node(format!(
+            "{} {} {} {} {}",
+            level, children[0], children[1], children[2], children[3]
+        ))
+    }
+}
+// Writes `pattern` as macrocell under `rule`, using 8x8 leaves for
+// two-state patterns and level 1 nodes otherwise.
+pub(crate) fn format_macrocell(pattern: &Pattern, rule: &str, multistate: bool) -> String {
+    let size = pattern.width.max(pattern.height).max(8);
+    let level = 32 - (size - 1).leading_zeros();
+    let mut writer = Writer {
+        grid: pattern
+            .cells
+            .iter()
+            .map(|&(row, col, state)| ((row, col), state))
+            .collect(),
+        multistate,
+        lines: Vec::new(),
+        seen: HashMap::new(),
+    };
+    let root = writer.build(level, 0, 0);
+    if root == 0 {
+        // an empty pattern still needs a root node
+        writer.lines.push(format!("{} 0 0 0 0", level + 1));
+    }
+    let mut text = format!("[M2] (wasm-module)\n#R {}\n", rule);
+    for line in writer.lines {
+        text.push_str(&line);
+        text.push('\n');
+    }
+    text
+}
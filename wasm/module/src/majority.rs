@@ -0,0 +1,46 @@
+use crate::rng::Rng;
+use crate::Cell;
+use wasm_bindgen::prelude::*;
+
+// How a majority-vote cell decides when its neighborhood is split evenly,
+// which happens along Dead edges where some of the neighborhood is missing.
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TieBreak {
+    #[default]
+    Keep = 0,
+    Alive = 1,
+    Dead = 2,
+    Random = 3,
+}
+
+// Majority vote over the cell and its neighbors: the cell takes whichever
+// state more than half of them hold, and with probability `noise` the other
+// one instead.
+pub(crate) fn majority_state(
+    state: u8,
+    alive: u32,
+    total: u32,
+    tie_break: TieBreak,
+    noise: f32,
+    rng: &mut Rng,
+) -> u8 {
+    let alive = match (2 * alive).cmp(&total) {
+        std::cmp::Ordering::Greater => true,
+        std::cmp::Ordering::Less => false,
+        std::cmp::Ordering::Equal => match tie_break {
+            TieBreak::Keep => state == Cell::Alive as u8,
+            TieBreak::Alive => true,
+            TieBreak::Dead => false,
+            TieBreak::Random => rng.next_u64() & 1 == 1,
+        },
+    };
+    let flipped = noise > 0.0 && rng.next_f32() < noise;
+    if alive != flipped {
+        Cell::Alive as u8
+    } else {
+        Cell::Dead as u8
+    }
+}
@@ -0,0 +1,83 @@
+use crate::isotropic::RING;
+use crate::Boundary;
+use wasm_bindgen::prelude::*;
+
+// Largest neighborhood radius anything accepts. A radius r makes every cell
+// read (2r + 1)^2 - 1 neighbors, so even this is slow on a large grid.
+pub(crate) const MAX_RADIUS: u32 = 500;
+
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Neighborhood {
+    // every cell within `radius` steps in both directions: the square
+    #[default]
+    Moore = 0,
+    // every cell within `radius` orthogonal steps: the diamond / cross
+    VonNeumann = 1,
+    // Hexagonal lattice stored as axial coordinates on the square grid: the
+    // (-1, +1) and (+1, -1) diagonals are not neighbors, leaving 6 at radius 1
+    Hexagonal = 2,
+}
+impl Neighborhood {
+    // (delta_row, delta_col) of every neighbor, excluding the cell itself
+    pub(crate) fn offsets(self, radius: u32) -> Vec<(i64, i64)> {
+        let r = radius as i64;
+        let mut offsets = Vec::new();
+        for delta_row in -r..=r {
+            for delta_col in -r..=r {
+                if delta_row == 0 && delta_col == 0 {
+                    continue;
+                }
+                let inside = match self {
+                    Neighborhood::Moore => true,
+                    Neighborhood::VonNeumann => delta_row.abs() + delta_col.abs() <= r,
+                    Neighborhood::Hexagonal => (delta_row - delta_col).abs() <= r,
+                };
+                if inside {
+                    offsets.push((delta_row, delta_col));
+                }
+            }
+        }
+        offsets
+    }
+}
+
+// Pixel center of the pointy-top hexagon with circumradius `size` for the cell
+// at (row, column) of a hexagonal universe `height` rows tall. Each row sits
+// half a hexagon left of the one above, turning the grid into a rhombus that
+// is shifted right so every center lies at a positive x.
+pub(crate) fn hex_center(row: u32, column: u32, height: u32, size: f64) -> (f64, f64) {
+    let hex_width = 3f64.sqrt() * size;
+    let shift = (height.saturating_sub(1) - row) as f64 / 2.0;
+    let x = hex_width * (column as f64 + shift + 0.5);
+    let y = size * (1.0 + 1.5 * row as f64);
+    (x, y)
+}
+
+// Cell indices of the neighbors at `offsets` from (row, column) in a
+// `width` x `height` grid, skipping those that fall off a dead edge.
+pub(crate) fn neighbor_indices(
+    offsets: &[(i64, i64)],
+    boundary: Boundary,
+    row: u32,
+    column: u32,
+    width: u32,
+    height: u32,
+) -> impl Iterator<Item = usize> + '_ {
+    offsets.iter().filter_map(move |&(delta_row, delta_col)| {
+        let row = boundary.resolve(row as i64 + delta_row, height)?;
+        let col = boundary.resolve(column as i64 + delta_col, width)?;
+        Some((row * width + col) as usize)
+    })
+}
+pub(crate) fn moore_neighbors(
+    boundary: Boundary,
+    row: u32,
+    column: u32,
+    width: u32,
+    height: u32,
+) -> impl Iterator<Item = usize> {
+    neighbor_indices(&RING, boundary, row, column, width, height)
+}
@@ -0,0 +1,82 @@
+use crate::snapshot::{push_varint, Reader, SnapshotError};
+
+const MAGIC: &[u8; 4] = b"LIFP";
+const VERSION: u8 = 1;
+
+// The cells that differ between two universes of the same size, and the
+// generation the second is at.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Patch {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) generation: u64,
+    // (index, state, color)
+    pub(crate) cells: Vec<(usize, u8, u8)>,
+}
+impl Patch {
+    pub(crate) fn between(
+        (width, height): (u32, u32),
+        before: (&[u8], &[u8]),
+        after: (&[u8], &[u8]),
+        generation: u64,
+    ) -> Patch {
+        let cells = (0..after.0.len())
+            .filter(|&idx| before.0[idx] != after.0[idx] || before.1[idx] != after.1[idx])
+            .map(|idx| (idx, after.0[idx], after.1[idx]))
+            .collect();
+        Patch {
+            width,
+            height,
+            generation,
+            cells,
+        }
+    }
+    // Each changed cell is the varint gap since the one before it, then its
+    // state and color, so a small change makes a small patch.
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.width.to_le_bytes());
+        bytes.extend_from_slice(&self.height.to_le_bytes());
+        bytes.extend_from_slice(&self.generation.to_le_bytes());
+        push_varint(&mut bytes, self.cells.len() as u64);
+        let mut next = 0;
+        for &(idx, state, color) in &self.cells {
+            push_varint(&mut bytes, (idx - next) as u64);
+            bytes.extend_from_slice(&[state, color]);
+            next = idx + 1;
+        }
+        bytes
+    }
+    pub(crate) fn decode(bytes: &[u8]) -> Result<Patch, SnapshotError> {
+        let mut reader = Reader::new(bytes);
+        if reader.take(MAGIC.len()).ok() != Some(&MAGIC[..]) {
+            return Err(SnapshotError::NotAPatch);
+        }
+        match reader.byte()? {
+            VERSION => {}
+            version => return Err(SnapshotError::UnsupportedVersion(version)),
+        }
+        let width = reader.u32()?;
+        let height = reader.u32()?;
+        let generation = reader.u64()?;
+        let len = width as u64 * height as u64;
+        let count = reader.varint()?;
+        let mut cells = Vec::new();
+        let mut next = 0u64;
+        for _ in 0..count {
+            let idx = match next.checked_add(reader.varint()?) {
+                Some(idx) if idx < len => idx,
+                _ => return Err(SnapshotError::InvalidField("patch cell")),
+            };
+            cells.push((idx as usize, reader.byte()?, reader.byte()?));
+            next = idx + 1;
+        }
+        Ok(Patch {
+            width,
+            height,
+            generation,
+            cells,
+        })
+    }
+}
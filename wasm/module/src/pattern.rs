@@ -0,0 +1,544 @@
+use crate::{apgcode, macrocell};
+use std::error::Error;
+use std::fmt;
+use wasm_bindgen::prelude::*;
+
+// Rotations are clockwise; flips mirror across the named axis.
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Transform {
+    #[default]
+    Identity = 0,
+    Rotate90 = 1,
+    Rotate180 = 2,
+    Rotate270 = 3,
+    FlipHorizontal = 4,
+    FlipVertical = 5,
+    // across the top-left to bottom-right diagonal
+    FlipDiagonal = 6,
+    FlipAntidiagonal = 7,
+}
+impl Transform {
+    pub(crate) fn rotation(quarter_turns: u32) -> Transform {
+        match quarter_turns % 4 {
+            1 => Transform::Rotate90,
+            2 => Transform::Rotate180,
+            3 => Transform::Rotate270,
+            _ => Transform::Identity,
+        }
+    }
+}
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    Horizontal = 0,
+    Vertical = 1,
+}
+impl Axis {
+    pub(crate) fn flip(self) -> Transform {
+        match self {
+            Axis::Horizontal => Transform::FlipHorizontal,
+            Axis::Vertical => Transform::FlipVertical,
+        }
+    }
+}
+
+// A pattern as the (row, column, state) of every non-dead cell inside a
+// `width` x `height` bounding box anchored at its top-left corner.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Pattern {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) cells: Vec<(u32, u32, u8)>,
+}
+impl Pattern {
+    // Builds a pattern from (row, column, state) cells at arbitrary, possibly
+    // negative coordinates, moving their bounding box to the origin.
+    pub(crate) fn from_coordinates(coordinates: &[(i64, i64, u8)]) -> Pattern {
+        let min_row = coordinates
+            .iter()
+            .map(|&(row, _, _)| row)
+            .min()
+            .unwrap_or(0);
+        let min_col = coordinates
+            .iter()
+            .map(|&(_, col, _)| col)
+            .min()
+            .unwrap_or(0);
+        let mut pattern = Pattern::default();
+        for &(row, col, state) in coordinates {
+            let (row, col) = ((row - min_row) as u32, (col - min_col) as u32);
+            pattern.width = pattern.width.max(col + 1);
+            pattern.height = pattern.height.max(row + 1);
+            pattern.cells.push((row, col, state));
+        }
+        pattern.cells.sort_unstable();
+        pattern.cells.dedup();
+        pattern
+    }
+    pub(crate) fn transformed(&self, transform: Transform) -> Pattern {
+        let (width, height) = (self.width, self.height);
+        let map = |row: u32, col: u32| match transform {
+            Transform::Identity => (row, col),
+            Transform::Rotate90 => (col, height - 1 - row),
+            Transform::Rotate180 => (height - 1 - row, width - 1 - col),
+            Transform::Rotate270 => (width - 1 - col, row),
+            Transform::FlipHorizontal => (row, width - 1 - col),
+            Transform::FlipVertical => (height - 1 - row, col),
+            Transform::FlipDiagonal => (col, row),
+            Transform::FlipAntidiagonal => (width - 1 - col, height - 1 - row),
+        };
+        let swapped = matches!(
+            transform,
+            Transform::Rotate90
+                | Transform::Rotate270
+                | Transform::FlipDiagonal
+                | Transform::FlipAntidiagonal
+        );
+        let mut cells: Vec<(u32, u32, u8)> = self
+            .cells
+            .iter()
+            .map(|&(row, col, state)| {
+                let (row, col) = map(row, col);
+                (row, col, state)
+            })
+            .collect();
+        cells.sort_unstable();
+        Pattern {
+            width: if swapped { height } else { width },
+            height: if swapped { width } else { height },
+            cells,
+        }
+    }
+    // rows of '.' for dead and 'O' for alive cells
+    fn from_rows(rows: &[&str]) -> Pattern {
+        let mut pattern = Pattern {
+            width: 0,
+            height: rows.len() as u32,
+            cells: Vec::new(),
+        };
+        for (row, line) in rows.iter().enumerate() {
+            pattern.width = pattern.width.max(line.len() as u32);
+            for (col, c) in line.chars().enumerate() {
+                if c == 'O' {
+                    pattern.cells.push((row as u32, col as u32, 1));
+                }
+            }
+        }
+        pattern
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PatternError {
+    UnknownPattern(String),
+    InvalidRle(String),
+    InvalidLife106(String),
+    InvalidPlaintext(char),
+    InvalidMacrocell(String),
+    InvalidApgcode(String),
+    UnsupportedRule(String),
+    NotPeriodic(u32),
+}
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PatternError::UnknownPattern(name) => write!(f, "unknown pattern {:?}", name),
+            PatternError::InvalidRle(reason) => write!(f, "invalid RLE: {}", reason),
+            PatternError::InvalidLife106(line) => write!(f, "invalid Life 1.06 line {:?}", line),
+            PatternError::InvalidPlaintext(c) => write!(f, "invalid plaintext cell {:?}", c),
+            PatternError::InvalidMacrocell(reason) => write!(f, "invalid macrocell: {}", reason),
+            PatternError::InvalidApgcode(code) => write!(f, "invalid apgcode {:?}", code),
+            PatternError::UnsupportedRule(rule) => {
+                write!(f, "rule {:?} is not a two-state Moore rule", rule)
+            }
+            PatternError::NotPeriodic(generations) => {
+                write!(
+                    f,
+                    "pattern does not repeat within {} generations",
+                    generations
+                )
+            }
+        }
+    }
+}
+impl Error for PatternError {}
+
+struct Builtin {
+    name: &'static str,
+    category: &'static str,
+    // 0 for patterns that never repeat
+    period: u32,
+    description: &'static str,
+    rows: &'static [&'static str],
+}
+const BUILTINS: [Builtin; 5] = [
+    Builtin {
+        name: "glider",
+        category: "spaceship",
+        period: 4,
+        description: "The smallest spaceship, moving diagonally at c/4.",
+        rows: &[".O.", "..O", "OOO"],
+    },
+    Builtin {
+        name: "lwss",
+        category: "spaceship",
+        period: 4,
+        description: "Lightweight spaceship, moving orthogonally at c/2.",
+        rows: &[".O..O", "O....", "O...O", "OOOO."],
+    },
+    Builtin {
+        name: "pulsar",
+        category: "oscillator",
+        period: 3,
+        description: "The most common period 3 oscillator.",
+        rows: &[
+            "..OOO...OOO..",
+            ".............",
+            "O....O.O....O",
+            "O....O.O....O",
+            "O....O.O....O",
+            "..OOO...OOO..",
+            ".............",
+            "..OOO...OOO..",
+            "O....O.O....O",
+            "O....O.O....O",
+            "O....O.O....O",
+            ".............",
+            "..OOO...OOO..",
+        ],
+    },
+    Builtin {
+        name: "gosper-glider-gun",
+        category: "gun",
+        period: 30,
+        description: "The first known gun, emitting a glider every 30 generations.",
+        rows: &[
+            "........................O...........",
+            "......................O.O...........",
+            "............OO......OO............OO",
+            "...........O...O....OO............OO",
+            "OO........O.....O...OO..............",
+            "OO........O...O.OO....O.O...........",
+            "..........O.....O.......O...........",
+            "...........O...O....................",
+            "............OO......................",
+        ],
+    },
+    Builtin {
+        name: "r-pentomino",
+        category: "methuselah",
+        period: 0,
+        description: "Five cells that take 1103 generations to stabilize.",
+        rows: &[".OO", "OO.", ".O."],
+    },
+];
+
+// Catalogue entry for a built-in pattern.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct PatternInfo {
+    name: &'static str,
+    category: &'static str,
+    width: u32,
+    height: u32,
+    period: u32,
+    description: &'static str,
+}
+#[wasm_bindgen]
+impl PatternInfo {
+    pub fn name(&self) -> String {
+        self.name.to_string()
+    }
+    pub fn category(&self) -> String {
+        self.category.to_string()
+    }
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    // 0 for patterns that never repeat
+    pub fn period(&self) -> u32 {
+        self.period
+    }
+    pub fn description(&self) -> String {
+        self.description.to_string()
+    }
+}
+
+pub(crate) fn builtin_names() -> impl Iterator<Item = &'static str> {
+    BUILTINS.iter().map(|builtin| builtin.name)
+}
+// Built-ins whose name, category or description contains `query`, ignoring
+// case; an empty query matches everything.
+pub(crate) fn catalogue(query: &str) -> Vec<PatternInfo> {
+    let query = query.trim().to_ascii_lowercase();
+    BUILTINS
+        .iter()
+        .filter(|builtin| {
+            [builtin.name, builtin.category, builtin.description]
+                .iter()
+                .any(|field| field.to_ascii_lowercase().contains(&query))
+        })
+        .map(|builtin| {
+            let pattern = Pattern::from_rows(builtin.rows);
+            PatternInfo {
+                name: builtin.name,
+                category: builtin.category,
+                width: pattern.width,
+                height: pattern.height,
+                period: builtin.period,
+                description: builtin.description,
+            }
+        })
+        .collect()
+}
+// Looks a built-in up by name, ignoring case and treating spaces and
+// underscores like dashes.
+pub(crate) fn builtin(name: &str) -> Result<Pattern, PatternError> {
+    let key = name.trim().to_ascii_lowercase().replace([' ', '_'], "-");
+    BUILTINS
+        .iter()
+        .find(|builtin| builtin.name == key)
+        .map(|builtin| Pattern::from_rows(builtin.rows))
+        .ok_or_else(|| PatternError::UnknownPattern(name.to_string()))
+}
+
+// Parses Golly/LifeWiki RLE: '#' comment lines, an optional "x = .., y = .."
+// header and then runs of 'b' (dead), 'o' (alive), '$' (end of row) up to a
+// closing '!'. Multi-state patterns use '.' for dead and 'A'..'X' or a
+// 'p'..'y' prefix plus 'A'..'X' for states 1 to 255. Any rule in the header
+// is ignored; the pattern takes on the universe's rule. `limit` caps both
+// the live cells and how many rows and columns the pattern spans, so a
+// short run count can't ask for more than the caller can hold.
+pub(crate) fn parse_rle(rle: &str, limit: usize) -> Result<Pattern, PatternError> {
+    let invalid = |reason: &str| PatternError::InvalidRle(reason.to_string());
+    let mut pattern = Pattern::default();
+    let mut body = String::new();
+    let mut header = None;
+    for line in rle.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if header.is_none() && body.is_empty() && line.starts_with(['x', 'X']) {
+            header = Some(line);
+            continue;
+        }
+        body.push_str(line);
+    }
+    if let Some(header) = header {
+        for field in header.split(',') {
+            let mut parts = field.splitn(2, '=').map(str::trim);
+            let (key, value) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+            let size = || match value.parse::<u32>() {
+                Ok(size) if size as usize <= limit => Ok(size),
+                Ok(_) => Err(invalid("size in header too large")),
+                Err(_) => Err(invalid("bad size in header")),
+            };
+            match key {
+                "x" | "X" => pattern.width = size()?,
+                "y" | "Y" => pattern.height = size()?,
+                _ => {}
+            }
+        }
+    }
+    let too_far = || PatternError::InvalidRle(format!("pattern spans more than {} cells", limit));
+    let (mut row, mut col) = (0u32, 0u32);
+    let mut run = 0u32;
+    let mut prefix = None;
+    for c in body.chars() {
+        let count = run.max(1);
+        let state = match c {
+            '0'..='9' if prefix.is_none() => {
+                run = run
+                    .checked_mul(10)
+                    .and_then(|run| run.checked_add(c as u32 - '0' as u32))
+                    .ok_or_else(|| invalid("run count too large"))?;
+                continue;
+            }
+            c if c.is_whitespace() => continue,
+            'p'..='y' if prefix.is_none() => {
+                prefix = Some(c as u32 - 'p' as u32 + 1);
+                continue;
+            }
+            '!' => break,
+            '$' => {
+                row = row
+                    .checked_add(count)
+                    .filter(|&row| (row as usize) < limit)
+                    .ok_or_else(too_far)?;
+                col = 0;
+                run = 0;
+                continue;
+            }
+            'b' | '.' if prefix.is_none() => 0,
+            'o' if prefix.is_none() => 1,
+            'A'..='X' => {
+                let state = prefix.take().unwrap_or(0) * 24 + (c as u32 - 'A' as u32 + 1);
+                if state > 255 {
+                    return Err(invalid("state above 255"));
+                }
+                state as u8
+            }
+            _ => return Err(PatternError::InvalidRle(format!("unexpected {:?}", c))),
+        };
+        let end = col
+            .checked_add(count)
+            .filter(|&end| end as usize <= limit)
+            .ok_or_else(too_far)?;
+        if state != 0 {
+            if count as usize > limit - pattern.cells.len() {
+                return Err(PatternError::InvalidRle(format!(
+                    "more than {} live cells",
+                    limit
+                )));
+            }
+            for offset in col..end {
+                pattern.cells.push((row, offset, state));
+            }
+        }
+        col = end;
+        run = 0;
+        pattern.width = pattern.width.max(col);
+        pattern.height = pattern.height.max(row + 1);
+    }
+    if prefix.is_some() {
+        return Err(invalid("state prefix without a state"));
+    }
+    Ok(pattern)
+}
+
+// Parses Life 1.06: a "#Life 1.06" header and any other '#' lines, then one
+// "x y" pair per live cell, where x is the column and y the row.
+pub(crate) fn parse_life106(text: &str) -> Result<Pattern, PatternError> {
+    let mut coordinates = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        // i32 keeps the span between any two cells within u32
+        let mut fields = line.split_whitespace().map(|field| field.parse::<i32>());
+        match (fields.next(), fields.next(), fields.next()) {
+            (Some(Ok(x)), Some(Ok(y)), None) => coordinates.push((y as i64, x as i64, 1)),
+            _ => return Err(PatternError::InvalidLife106(line.to_string())),
+        }
+    }
+    Ok(Pattern::from_coordinates(&coordinates))
+}
+
+// Parses the plaintext .cells format: '!' comment lines, then one line per
+// row with '.' for dead and 'O' for alive cells.
+pub(crate) fn parse_plaintext(text: &str) -> Result<Pattern, PatternError> {
+    let rows: Vec<&str> = text
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.starts_with('!'))
+        .collect();
+    if let Some(c) = rows
+        .iter()
+        .flat_map(|row| row.chars())
+        .find(|&c| c != '.' && c != 'O')
+    {
+        return Err(PatternError::InvalidPlaintext(c));
+    }
+    Ok(Pattern::from_rows(&rows))
+}
+
+// Parses text in whichever format it looks like: macrocell, Life 1.06, an
+// apgcode, plaintext or, failing all of those, RLE. `limit` caps the live
+// cells of a macrocell or RLE pattern.
+pub(crate) fn parse_any(text: &str, limit: usize) -> Result<Pattern, PatternError> {
+    let text = text.trim();
+    let is_plaintext =
+        |line: &str| line.starts_with('!') || line.chars().all(|c| c == '.' || c == 'O');
+    if text.starts_with("[M2]") {
+        macrocell::parse_macrocell(text, limit)
+    } else if text.starts_with("#Life 1.06") {
+        parse_life106(text)
+    } else if apgcode::looks_like(text) {
+        apgcode::decode(text)
+    } else if text.lines().map(str::trim_end).all(is_plaintext) {
+        parse_plaintext(text)
+    } else {
+        parse_rle(text, limit)
+    }
+}
+
+fn rle_tag(state: u8, multistate: bool) -> String {
+    match (state, multistate) {
+        (0, false) => "b".to_string(),
+        (_, false) => "o".to_string(),
+        (0, true) => ".".to_string(),
+        (state, true) => {
+            let (prefix, letter) = ((state - 1) / 24, (state - 1) % 24);
+            let letter = (b'A' + letter) as char;
+            if prefix == 0 {
+                letter.to_string()
+            } else {
+                format!("{}{}", (b'p' + prefix - 1) as char, letter)
+            }
+        }
+    }
+}
+fn push_token(count: u32, tag: &str, line: &mut String, rle: &mut String) {
+    let token = if count > 1 {
+        format!("{}{}", count, tag)
+    } else {
+        tag.to_string()
+    };
+    if line.len() + token.len() > 70 {
+        rle.push_str(line);
+        rle.push('\n');
+        line.clear();
+    }
+    line.push_str(&token);
+}
+// Writes `pattern` as RLE with a header naming `rule`, wrapping lines at 70
+// characters as Golly does. `multistate` switches to the '.'/'A'..'X' tags.
+pub(crate) fn format_rle(pattern: &Pattern, rule: &str, multistate: bool) -> String {
+    let mut rle = format!(
+        "x = {}, y = {}, rule = {}\n",
+        pattern.width, pattern.height, rule
+    );
+    let mut line = String::new();
+    let mut cells = pattern.cells.clone();
+    cells.sort_unstable();
+    let (mut row, mut col) = (0, 0);
+    let mut run: Option<(u8, u32)> = None;
+    for &(cell_row, cell_col, state) in cells.iter() {
+        if cell_row > row {
+            if let Some((state, count)) = run.take() {
+                push_token(count, &rle_tag(state, multistate), &mut line, &mut rle);
+            }
+            push_token(cell_row - row, "$", &mut line, &mut rle);
+            row = cell_row;
+            col = 0;
+        }
+        if cell_col > col {
+            if let Some((state, count)) = run.take() {
+                push_token(count, &rle_tag(state, multistate), &mut line, &mut rle);
+            }
+            run = Some((0, cell_col - col));
+        }
+        run = match run {
+            Some((run_state, count)) if run_state == state => Some((state, count + 1)),
+            Some((run_state, count)) => {
+                push_token(count, &rle_tag(run_state, multistate), &mut line, &mut rle);
+                Some((state, 1))
+            }
+            None => Some((state, 1)),
+        };
+        col = cell_col + 1;
+    }
+    if let Some((state, count)) = run {
+        push_token(count, &rle_tag(state, multistate), &mut line, &mut rle);
+    }
+    push_token(1, "!", &mut line, &mut rle);
+    rle.push_str(&line);
+    rle.push('\n');
+    rle
+}
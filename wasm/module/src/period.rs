@@ -0,0 +1,57 @@
+use std::collections::VecDeque;
+
+// how many generations back a repeat is looked for, and so the longest
+// period that can be detected
+const WINDOW: usize = 256;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+// 64-bit FNV-1a over the cell states.
+pub(crate) fn hash(cells: &[u8]) -> u64 {
+    fnv1a(FNV_OFFSET, cells)
+}
+// hash() that also takes in the grid's dimensions, so the same cells laid out
+// on differently shaped grids hash apart.
+pub(crate) fn hash_sized(width: u32, height: u32, cells: &[u8]) -> u64 {
+    let hash = fnv1a(FNV_OFFSET, &width.to_le_bytes());
+    let hash = fnv1a(hash, &height.to_le_bytes());
+    fnv1a(hash, cells)
+}
+
+// Hashes of the latest generations, newest last. A generation whose hash
+// showed up `n` generations earlier puts the universe in a cycle of period
+// `n`, barring a hash collision.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Periods {
+    hashes: VecDeque<u64>,
+    period: Option<u32>,
+}
+impl Periods {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+    pub(crate) fn push(&mut self, hash: u64) {
+        self.period = self
+            .hashes
+            .iter()
+            .rev()
+            .position(|&earlier| earlier == hash)
+            .map(|back| back as u32 + 1);
+        if self.hashes.len() == WINDOW {
+            self.hashes.pop_front();
+        }
+        self.hashes.push_back(hash);
+    }
+    pub(crate) fn period(&self) -> Option<u32> {
+        self.period
+    }
+    pub(crate) fn clear(&mut self) {
+        self.hashes.clear();
+        self.period = None;
+    }
+}
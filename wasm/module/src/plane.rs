@@ -0,0 +1,143 @@
+use crate::pattern::PatternError;
+use crate::{Cell, Rule, RING};
+use std::collections::HashSet;
+
+// (row, column) of the top-left corner of the bounding box of `cells`
+pub(crate) fn origin(cells: &[(i64, i64)]) -> (i64, i64) {
+    (
+        cells.iter().map(|&(row, _)| row).min().unwrap_or(0),
+        cells.iter().map(|&(_, col)| col).min().unwrap_or(0),
+    )
+}
+// `cells` sorted and moved so their bounding box starts at 0
+pub(crate) fn normalize(cells: &[(i64, i64)]) -> Vec<(i64, i64)> {
+    let (top, left) = origin(cells);
+    let mut cells: Vec<(i64, i64)> = cells
+        .iter()
+        .map(|&(row, col)| (row - top, col - left))
+        .collect();
+    cells.sort_unstable();
+    cells
+}
+
+// Fails for rules that can't run on an unbounded plane: anything but a
+// two-state rule on the radius 1 Moore neighborhood.
+pub(crate) fn check_rule(rule: &Rule) -> Result<(), PatternError> {
+    match rule {
+        Rule::LifeLike(life) if life.states() == 2 => Ok(()),
+        Rule::Isotropic(isotropic) if isotropic.states() == 2 => Ok(()),
+        _ => Err(PatternError::UnsupportedRule(rule.to_string())),
+    }
+}
+// Fails for rules check_rule() does, and for B0 rules too: under those every
+// empty cell comes alive, which no finite set of live cells can hold.
+pub(crate) fn check_unbounded_rule(rule: &Rule) -> Result<(), PatternError> {
+    check_rule(rule)?;
+    if next_alive(rule, false, 0) {
+        return Err(PatternError::UnsupportedRule(rule.to_string()));
+    }
+    Ok(())
+}
+// Whether a cell is alive next generation under a rule check_rule()
+// accepts, with `config` packing its live neighbors in RING order.
+pub(crate) fn next_alive(rule: &Rule, alive: bool, config: u8) -> bool {
+    let state = alive as u8;
+    let next = match rule {
+        Rule::LifeLike(rule) => rule.next_state(state, config.count_ones()),
+        Rule::Isotropic(rule) => rule.next_state(state, config),
+        _ => Cell::Dead as u8,
+    };
+    next == Cell::Alive as u8
+}
+
+// Live cells run on an unbounded plane, away from the grid and its boundary,
+// under a two-state rule on the radius 1 Moore neighborhood without B0.
+pub(crate) struct Plane {
+    rule: Rule,
+    live: HashSet<(i64, i64)>,
+}
+impl Plane {
+    pub(crate) fn new(rule: &Rule, cells: &[(i64, i64)]) -> Result<Plane, PatternError> {
+        check_unbounded_rule(rule)?;
+        Ok(Plane {
+            rule: *rule,
+            live: cells.iter().copied().collect(),
+        })
+    }
+    pub(crate) fn step(&mut self) {
+        let mut candidates = self.live.clone();
+        for &(row, col) in self.live.iter() {
+            candidates.extend(RING.iter().map(|&(dr, dc)| (row + dr, col + dc)));
+        }
+        let live = &self.live;
+        let rule = &self.rule;
+        self.live = candidates
+            .into_iter()
+            .filter(|&(row, col)| {
+                let mut config = 0u8;
+                for (bit, &(dr, dc)) in RING.iter().enumerate() {
+                    config |= (live.contains(&(row + dr, col + dc)) as u8) << bit;
+                }
+                next_alive(rule, live.contains(&(row, col)), config)
+            })
+            .collect();
+    }
+    pub(crate) fn rule(&self) -> Rule {
+        self.rule
+    }
+    pub(crate) fn len(&self) -> usize {
+        self.live.len()
+    }
+    pub(crate) fn is_alive(&self, row: i64, col: i64) -> bool {
+        self.live.contains(&(row, col))
+    }
+    pub(crate) fn set(&mut self, row: i64, col: i64, alive: bool) {
+        if alive {
+            self.live.insert((row, col));
+        } else {
+            self.live.remove(&(row, col));
+        }
+    }
+    pub(crate) fn clear(&mut self) {
+        self.live.clear();
+    }
+    pub(crate) fn cells(&self) -> Vec<(i64, i64)> {
+        let mut cells: Vec<(i64, i64)> = self.live.iter().copied().collect();
+        cells.sort_unstable();
+        cells
+    }
+}
+
+// How `cells` repeat: the period, how far the pattern moves per period, and
+// every phase in order starting with `cells` themselves.
+pub(crate) struct Motion {
+    pub(crate) period: u32,
+    pub(crate) displacement: (i64, i64),
+    pub(crate) phases: Vec<Vec<(i64, i64)>>,
+}
+// Runs `cells` until they repeat their first generation, up to `max_period`
+// generations.
+pub(crate) fn find_motion(
+    rule: &Rule,
+    cells: &[(i64, i64)],
+    max_period: u32,
+) -> Result<Motion, PatternError> {
+    let mut plane = Plane::new(rule, cells)?;
+    let start = normalize(cells);
+    let (top, left) = origin(cells);
+    let mut phases = vec![cells.to_vec()];
+    for period in 1..=max_period {
+        plane.step();
+        let current = plane.cells();
+        if normalize(&current) == start {
+            let (row, col) = origin(&current);
+            return Ok(Motion {
+                period,
+                displacement: (row - top, col - left),
+                phases,
+            });
+        }
+        phases.push(current);
+    }
+    Err(PatternError::NotPeriodic(max_period))
+}
@@ -0,0 +1,67 @@
+use wasm_bindgen::prelude::*;
+use web_sys::CanvasRenderingContext2d;
+
+// Fills a `cell_size` square for every cell, laid out like fit() expects:
+// each cell followed by a 1px grid line, after a leading one. A cell gets
+// `colors[state]`, or the last color for states past the end. Cells are
+// drawn a color at a time so the fill style changes once per color.
+pub(crate) fn draw(
+    context: &CanvasRenderingContext2d,
+    cells: &[u8],
+    width: u32,
+    cell_size: u32,
+    colors: &[String],
+) -> Result<(), JsError> {
+    let last = match colors.len().checked_sub(1) {
+        Some(last) => last,
+        None => return Err(JsError::new("no colors to draw with")),
+    };
+    let (size, step) = (cell_size as f64, cell_size as f64 + 1.0);
+    for (color_idx, color) in colors.iter().enumerate() {
+        context.set_fill_style_str(color);
+        for (idx, &state) in cells.iter().enumerate() {
+            if (state as usize).min(last) != color_idx {
+                continue;
+            }
+            let (row, col) = (idx as u32 / width, idx as u32 % width);
+            context.fill_rect(col as f64 * step + 1.0, row as f64 * step + 1.0, size, size);
+        }
+    }
+    Ok(())
+}
+
+// Fills `rgba` with the cells as `scale` x `scale` blocks of RGBA pixels,
+// ready for an ImageData `width * scale` pixels wide. A cell gets
+// `colors[state]` as 0xRRGGBBAA, or the last color for states past the end.
+pub(crate) fn rgba(
+    rgba: &mut Vec<u8>,
+    cells: &[u8],
+    width: u32,
+    scale: u32,
+    colors: &[u32],
+) -> Result<(), JsError> {
+    let last = match colors.len().checked_sub(1) {
+        Some(last) => last,
+        None => return Err(JsError::new("no colors to draw with")),
+    };
+    let (width, scale) = (width as usize, scale as usize);
+    let row_bytes = width * scale * 4;
+    rgba.clear();
+    rgba.reserve(cells.len() * scale * scale * 4);
+    if width == 0 {
+        return Ok(());
+    }
+    for line in cells.chunks(width) {
+        let start = rgba.len();
+        for &state in line {
+            let pixel = colors[(state as usize).min(last)].to_be_bytes();
+            for _ in 0..scale {
+                rgba.extend_from_slice(&pixel);
+            }
+        }
+        for _ in 1..scale {
+            rgba.extend_from_within(start..start + row_bytes);
+        }
+    }
+    Ok(())
+}
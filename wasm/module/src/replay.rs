@@ -0,0 +1,248 @@
+use crate::neighborhood::MAX_RADIUS;
+use crate::snapshot::{push_string, push_varint, Reader, SnapshotError, MAX_CELLS};
+use crate::{Boundary, ColorVariant, Neighborhood, TieBreak};
+
+const MAGIC: &[u8; 4] = b"LIFR";
+const VERSION: u8 = 1;
+
+// A log is a version-1 header, the save() snapshot the run started from and
+// the settings a snapshot leaves out, then events, each a tag byte and its
+// fields. Edits are logged by the cells they changed rather than the calls
+// that made them, so any way of editing replays the same.
+const TICKS: u8 = 0;
+const CELLS: u8 = 1;
+const RULE: u8 = 2;
+const NEIGHBORHOOD: u8 = 3;
+const BOUNDARY: u8 = 4;
+const TIE_BREAK: u8 = 5;
+const NOISE: u8 = 6;
+const BIRTH_PROBABILITY: u8 = 7;
+const SURVIVAL_PROBABILITY: u8 = 8;
+const RESEED: u8 = 9;
+const RESIZE: u8 = 10;
+const COLOR_VARIANT: u8 = 11;
+const FREEZE_WHEN_STABLE: u8 = 12;
+
+// Everything a log records, in the order the run did it.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Event {
+    Ticks(u64),
+    // (index, state, color) of every changed cell
+    Cells(Vec<(usize, u8, u8)>),
+    Rule(String),
+    Neighborhood(Neighborhood, u32),
+    Boundary(Boundary),
+    TieBreak(TieBreak),
+    Noise(f32),
+    BirthProbability(f32),
+    SurvivalProbability(f32),
+    Reseed(u64),
+    Resize(u32, u32),
+    ColorVariant(ColorVariant, u64),
+    FreezeWhenStable(bool),
+}
+impl Event {
+    fn encode(&self, bytes: &mut Vec<u8>) {
+        match self {
+            Event::Ticks(n) => {
+                bytes.push(TICKS);
+                push_varint(bytes, *n);
+            }
+            Event::Cells(cells) => {
+                bytes.push(CELLS);
+                push_varint(bytes, cells.len() as u64);
+                for &(idx, state, color) in cells {
+                    push_varint(bytes, idx as u64);
+                    bytes.extend_from_slice(&[state, color]);
+                }
+            }
+            Event::Rule(rule) => {
+                bytes.push(RULE);
+                push_string(bytes, rule);
+            }
+            Event::Neighborhood(neighborhood, radius) => {
+                bytes.extend_from_slice(&[NEIGHBORHOOD, *neighborhood as u8]);
+                bytes.extend_from_slice(&radius.to_le_bytes());
+            }
+            Event::Boundary(boundary) => bytes.extend_from_slice(&[BOUNDARY, *boundary as u8]),
+            Event::TieBreak(tie_break) => bytes.extend_from_slice(&[TIE_BREAK, *tie_break as u8]),
+            Event::Noise(noise) => {
+                bytes.push(NOISE);
+                bytes.extend_from_slice(&noise.to_le_bytes());
+            }
+            Event::BirthProbability(probability) => {
+                bytes.push(BIRTH_PROBABILITY);
+                bytes.extend_from_slice(&probability.to_le_bytes());
+            }
+            Event::SurvivalProbability(probability) => {
+                bytes.push(SURVIVAL_PROBABILITY);
+                bytes.extend_from_slice(&probability.to_le_bytes());
+            }
+            Event::Reseed(seed) => {
+                bytes.push(RESEED);
+                bytes.extend_from_slice(&seed.to_le_bytes());
+            }
+            Event::Resize(width, height) => {
+                bytes.push(RESIZE);
+                bytes.extend_from_slice(&width.to_le_bytes());
+                bytes.extend_from_slice(&height.to_le_bytes());
+            }
+            Event::ColorVariant(variant, seed) => {
+                bytes.extend_from_slice(&[COLOR_VARIANT, *variant as u8]);
+                bytes.extend_from_slice(&seed.to_le_bytes());
+            }
+            Event::FreezeWhenStable(freeze) => {
+                bytes.extend_from_slice(&[FREEZE_WHEN_STABLE, *freeze as u8])
+            }
+        }
+    }
+    fn decode(reader: &mut Reader) -> Result<Event, SnapshotError> {
+        Ok(match reader.byte()? {
+            TICKS => Event::Ticks(reader.varint()?),
+            CELLS => {
+                let count = reader.varint()?;
+                let mut cells = Vec::new();
+                for _ in 0..count {
+                    let idx = reader.varint()? as usize;
+                    cells.push((idx, reader.byte()?, reader.byte()?));
+                }
+                Event::Cells(cells)
+            }
+            RULE => Event::Rule(reader.string("rule")?.to_string()),
+            NEIGHBORHOOD => {
+                let neighborhood = reader.neighborhood()?;
+                // set_neighborhood() clamps, but logs it the way it was called
+                match reader.u32()? {
+                    radius if radius <= MAX_RADIUS => Event::Neighborhood(neighborhood, radius),
+                    _ => return Err(SnapshotError::InvalidField("radius")),
+                }
+            }
+            BOUNDARY => Event::Boundary(reader.boundary()?),
+            TIE_BREAK => Event::TieBreak(reader.tie_break()?),
+            NOISE => Event::Noise(reader.f32()?),
+            BIRTH_PROBABILITY => Event::BirthProbability(reader.f32()?),
+            SURVIVAL_PROBABILITY => Event::SurvivalProbability(reader.f32()?),
+            RESEED => Event::Reseed(reader.u64()?),
+            RESIZE => {
+                let (width, height) = (reader.u32()?, reader.u32()?);
+                if width as u64 * height as u64 > MAX_CELLS {
+                    return Err(SnapshotError::InvalidField("dimensions"));
+                }
+                Event::Resize(width, height)
+            }
+            COLOR_VARIANT => Event::ColorVariant(reader.color_variant()?, reader.u64()?),
+            FREEZE_WHEN_STABLE => Event::FreezeWhenStable(reader.byte()? != 0),
+            _ => return Err(SnapshotError::InvalidField("replay event")),
+        })
+    }
+}
+
+// What a universe was like when recording started, beyond its snapshot.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Start {
+    pub(crate) snapshot: Vec<u8>,
+    pub(crate) rng: u64,
+    pub(crate) noise: f32,
+    pub(crate) birth_probability: f32,
+    pub(crate) survival_probability: f32,
+    pub(crate) freeze_when_stable: bool,
+}
+
+// Builds up a log while a universe runs. It keeps its own copy of the cells
+// and colors as of the last event to find what edits changed since.
+#[derive(Clone, Debug)]
+pub(crate) struct Recorder {
+    log: Vec<u8>,
+    cells: Vec<u8>,
+    colors: Vec<u8>,
+    ticks: u64,
+}
+fn changed_cells(before: (&[u8], &[u8]), after: (&[u8], &[u8])) -> Vec<(usize, u8, u8)> {
+    (0..after.0.len())
+        .filter(|&idx| {
+            before.0.get(idx) != Some(&after.0[idx]) || before.1.get(idx) != Some(&after.1[idx])
+        })
+        .map(|idx| (idx, after.0[idx], after.1[idx]))
+        .collect()
+}
+impl Recorder {
+    pub(crate) fn new(start: &Start, cells: &[u8], colors: &[u8]) -> Recorder {
+        let mut log = MAGIC.to_vec();
+        log.push(VERSION);
+        push_varint(&mut log, start.snapshot.len() as u64);
+        log.extend_from_slice(&start.snapshot);
+        log.extend_from_slice(&start.rng.to_le_bytes());
+        log.extend_from_slice(&start.noise.to_le_bytes());
+        log.extend_from_slice(&start.birth_probability.to_le_bytes());
+        log.extend_from_slice(&start.survival_probability.to_le_bytes());
+        log.push(start.freeze_when_stable as u8);
+        Recorder {
+            log,
+            cells: cells.to_vec(),
+            colors: colors.to_vec(),
+            ticks: 0,
+        }
+    }
+    fn push(&mut self, event: Event) {
+        if self.ticks > 0 {
+            Event::Ticks(self.ticks).encode(&mut self.log);
+            self.ticks = 0;
+        }
+        event.encode(&mut self.log);
+    }
+    // Logs whatever edits changed since the last event.
+    pub(crate) fn edits(&mut self, cells: &[u8], colors: &[u8]) {
+        let changed = changed_cells((&self.cells, &self.colors), (cells, colors));
+        if !changed.is_empty() {
+            self.push(Event::Cells(changed));
+            self.sync(cells, colors);
+        }
+    }
+    pub(crate) fn event(&mut self, event: Event) {
+        self.push(event);
+    }
+    pub(crate) fn tick(&mut self) {
+        self.ticks += 1;
+    }
+    // Catches the copy up with changes that replaying the last event makes
+    // by itself, such as a tick's.
+    pub(crate) fn sync(&mut self, cells: &[u8], colors: &[u8]) {
+        self.cells.clear();
+        self.cells.extend_from_slice(cells);
+        self.colors.clear();
+        self.colors.extend_from_slice(colors);
+    }
+    pub(crate) fn export(&self, cells: &[u8], colors: &[u8]) -> Vec<u8> {
+        let mut recorder = self.clone();
+        recorder.edits(cells, colors);
+        if recorder.ticks > 0 {
+            Event::Ticks(recorder.ticks).encode(&mut recorder.log);
+        }
+        recorder.log
+    }
+}
+
+pub(crate) fn decode(log: &[u8]) -> Result<(Start, Vec<Event>), SnapshotError> {
+    let mut reader = Reader::new(log);
+    if reader.take(MAGIC.len()).ok() != Some(&MAGIC[..]) {
+        return Err(SnapshotError::NotAReplayLog);
+    }
+    match reader.byte()? {
+        VERSION => {}
+        version => return Err(SnapshotError::UnsupportedVersion(version)),
+    }
+    let len = reader.varint()? as usize;
+    let start = Start {
+        snapshot: reader.take(len)?.to_vec(),
+        rng: reader.u64()?,
+        noise: reader.f32()?,
+        birth_probability: reader.f32()?,
+        survival_probability: reader.f32()?,
+        freeze_when_stable: reader.byte()? != 0,
+    };
+    let mut events = Vec::new();
+    while !reader.is_empty() {
+        events.push(Event::decode(&mut reader)?);
+    }
+    Ok((start, events))
+}
@@ -0,0 +1,26 @@
+// SplitMix64: tiny, fast and fully deterministic for a given seed, which is
+// all the simulation needs to make random fills reproducible.
+#[derive(Clone, Debug)]
+pub(crate) struct Rng {
+    state: u64,
+}
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+    // the seed that would carry on from here
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+    // uniform in [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
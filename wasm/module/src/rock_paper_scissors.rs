@@ -0,0 +1,156 @@
+use crate::neighborhood::neighbor_indices;
+use crate::rng::Rng;
+use crate::{Boundary, Neighborhood};
+use wasm_bindgen::prelude::*;
+
+// Rock-paper-scissors ecosystem: species k beats species k + 1 (mod
+// `species`). Every tick each cell looks at one random neighbor and, if that
+// neighbor beats it, is invaded with probability `invasion`.
+#[wasm_bindgen]
+pub struct RockPaperScissors {
+    width: u32,
+    height: u32,
+    cells: Vec<u8>,
+    species: u8,
+    invasion: f32,
+    // living cells per species, kept in step with `cells`
+    populations: Vec<u32>,
+    neighborhood: Neighborhood,
+    neighbor_offsets: Vec<(i64, i64)>,
+    boundary: Boundary,
+    rng: Rng,
+    generation: u64,
+}
+#[wasm_bindgen]
+impl RockPaperScissors {
+    fn get_index(&self, row: u32, column: u32) -> usize {
+        (row * self.width + column) as usize
+    }
+    fn recount(&mut self) {
+        self.populations = vec![0; self.species as usize];
+        for &cell in self.cells.iter() {
+            self.populations[cell as usize] += 1;
+        }
+    }
+    pub fn new(width: u32, height: u32, seed: u64) -> RockPaperScissors {
+        let mut rps = RockPaperScissors {
+            width,
+            height,
+            cells: vec![0; (width * height) as usize],
+            species: 3,
+            invasion: 1.0,
+            populations: Vec::new(),
+            neighborhood: Neighborhood::Moore,
+            neighbor_offsets: Neighborhood::Moore.offsets(1),
+            boundary: Boundary::default(),
+            rng: Rng::new(seed),
+            generation: 0,
+        };
+        rps.randomize();
+        rps
+    }
+    pub fn tick(&mut self) {
+        let mut next = self.cells.clone();
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let idx = self.get_index(row, col);
+                let neighbors: Vec<usize> = neighbor_indices(
+                    &self.neighbor_offsets,
+                    self.boundary,
+                    row,
+                    col,
+                    self.width,
+                    self.height,
+                )
+                .collect();
+                if neighbors.is_empty() {
+                    continue;
+                }
+                let pick = (self.rng.next_u64() % neighbors.len() as u64) as usize;
+                let challenger = self.cells[neighbors[pick]];
+                let prey = self.cells[idx];
+                if (challenger + 1) % self.species == prey && self.rng.next_f32() < self.invasion {
+                    next[idx] = challenger;
+                    self.populations[prey as usize] -= 1;
+                    self.populations[challenger as usize] += 1;
+                }
+            }
+        }
+        self.cells = next;
+        self.generation += 1;
+    }
+    pub fn tick_n(&mut self, n: u32) {
+        for _ in 0..n {
+            self.tick();
+        }
+    }
+    // Every cell gets a uniformly random species.
+    pub fn randomize(&mut self) {
+        for idx in 0..self.cells.len() {
+            self.cells[idx] = (self.rng.next_u64() % self.species as u64) as u8;
+        }
+        self.recount();
+        self.generation = 0;
+    }
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    pub fn species(&self) -> u8 {
+        self.species
+    }
+    // Cells of species the new cycle doesn't have wrap around into it.
+    pub fn set_species(&mut self, species: u8) {
+        self.species = species.max(3);
+        let species = self.species;
+        self.cells.iter_mut().for_each(|cell| *cell %= species);
+        self.recount();
+    }
+    pub fn invasion(&self) -> f32 {
+        self.invasion
+    }
+    pub fn set_invasion(&mut self, invasion: f32) {
+        self.invasion = invasion.clamp(0.0, 1.0);
+    }
+    pub fn neighborhood(&self) -> Neighborhood {
+        self.neighborhood
+    }
+    pub fn set_neighborhood(&mut self, neighborhood: Neighborhood) {
+        self.neighborhood = neighborhood;
+        self.neighbor_offsets = neighborhood.offsets(1);
+    }
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+    }
+    pub fn get_state(&self, row: u32, column: u32) -> u8 {
+        self.cells[self.get_index(row, column)]
+    }
+    pub fn set_state(&mut self, row: u32, column: u32, species: u8) {
+        let idx = self.get_index(row, column);
+        let species = species % self.species;
+        self.populations[self.cells[idx] as usize] -= 1;
+        self.populations[species as usize] += 1;
+        self.cells[idx] = species;
+    }
+    pub fn population(&self, species: u8) -> u32 {
+        self.populations.get(species as usize).copied().unwrap_or(0)
+    }
+    // one count per species, in species order
+    pub fn populations(&self) -> Vec<u32> {
+        self.populations.clone()
+    }
+    pub fn cells(&self) -> js_sys::Uint8Array {
+        unsafe { js_sys::Uint8Array::view(&self.cells) }
+    }
+}
@@ -0,0 +1,320 @@
+use crate::isotropic::{is_isotropic, IsotropicRule};
+use crate::ltl::LtlRule;
+use crate::Cell;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use wasm_bindgen::prelude::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rule {
+    LifeLike(LifeLikeRule),
+    LargerThanLife(LtlRule),
+    Isotropic(IsotropicRule),
+    // majority vote over the neighborhood and the cell itself
+    Majority,
+}
+impl Rule {
+    pub fn states(&self) -> u8 {
+        match self {
+            Rule::LifeLike(rule) => rule.states(),
+            Rule::LargerThanLife(rule) => rule.states(),
+            Rule::Isotropic(rule) => rule.states(),
+            Rule::Majority => 2,
+        }
+    }
+}
+impl Default for Rule {
+    fn default() -> Self {
+        Rule::LifeLike(LifeLikeRule::CONWAY)
+    }
+}
+impl FromStr for Rule {
+    type Err = RuleError;
+    // "R..." comma-separated rulestrings are Larger than Life, B/S notation
+    // with Hensel letters is isotropic non-totalistic, "Majority" is the
+    // majority vote, and the rest Life-like.
+    fn from_str(rule: &str) -> Result<Rule, RuleError> {
+        let rule = rule.trim();
+        if rule.eq_ignore_ascii_case("majority") {
+            Ok(Rule::Majority)
+        } else if rule.starts_with(['R', 'r']) && rule.contains(',') {
+            Ok(Rule::LargerThanLife(rule.parse()?))
+        } else if is_isotropic(rule) {
+            Ok(Rule::Isotropic(rule.parse()?))
+        } else {
+            Ok(Rule::LifeLike(rule.parse()?))
+        }
+    }
+}
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Rule::LifeLike(rule) => rule.fmt(f),
+            Rule::LargerThanLife(rule) => rule.fmt(f),
+            Rule::Isotropic(rule) => rule.fmt(f),
+            Rule::Majority => write!(f, "Majority"),
+        }
+    }
+}
+
+// Shared by every totalistic family: with more than 2 `states` a live cell
+// that fails to survive passes through the dying states 2..states before it
+// is dead, and only state 1 counts as a live neighbor.
+pub(crate) fn next_state(state: u8, born: bool, survives: bool, states: u8) -> u8 {
+    const DEAD: u8 = Cell::Dead as u8;
+    const ALIVE: u8 = Cell::Alive as u8;
+    match state {
+        DEAD if born => ALIVE,
+        DEAD => DEAD,
+        ALIVE if survives => ALIVE,
+        dying if dying + 1 < states => dying + 1,
+        _ => DEAD,
+    }
+}
+
+// Life-like rule in B/S notation. Bit `n` of `birth` is set when a dead cell
+// with `n` live neighbors is born, and likewise for `survival`. `states`
+// above 2 makes it a Generations rule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LifeLikeRule {
+    birth: u16,
+    survival: u16,
+    states: u8,
+}
+impl LifeLikeRule {
+    pub const CONWAY: LifeLikeRule = LifeLikeRule {
+        birth: 1 << 3,
+        survival: 1 << 2 | 1 << 3,
+        states: 2,
+    };
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+    pub fn next_state(&self, state: u8, live_neighbors: u32) -> u8 {
+        next_state(
+            state,
+            has_count(self.birth, live_neighbors),
+            has_count(self.survival, live_neighbors),
+            self.states,
+        )
+    }
+}
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RuleError {
+    Malformed(String),
+    InvalidCount(char),
+    InvalidStates(String),
+    InvalidRadius(String),
+    InvalidRange(String),
+}
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuleError::Malformed(rule) => write!(f, "malformed rulestring {:?}", rule),
+            RuleError::InvalidCount(c) => write!(f, "invalid neighbor count {:?}", c),
+            RuleError::InvalidStates(states) => write!(f, "invalid state count {:?}", states),
+            RuleError::InvalidRadius(radius) => write!(f, "invalid radius {:?}", radius),
+            RuleError::InvalidRange(range) => write!(f, "invalid neighbor range {:?}", range),
+        }
+    }
+}
+impl Error for RuleError {}
+
+// Larger neighborhoods can see more than 8 live neighbors, which no B/S
+// digit can ask for.
+fn has_count(counts: u16, live_neighbors: u32) -> bool {
+    live_neighbors <= 8 && counts & (1 << live_neighbors) != 0
+}
+fn parse_counts(digits: &str) -> Result<u16, RuleError> {
+    let mut counts = 0;
+    for c in digits.chars() {
+        match c.to_digit(10) {
+            Some(n) if n <= 8 => counts |= 1 << n,
+            _ => return Err(RuleError::InvalidCount(c)),
+        }
+    }
+    Ok(counts)
+}
+pub(crate) fn parse_states(digits: &str) -> Result<u8, RuleError> {
+    match digits.parse() {
+        Ok(states) if states >= 2 => Ok(states),
+        _ => Err(RuleError::InvalidStates(digits.to_string())),
+    }
+}
+fn format_counts(f: &mut fmt::Formatter, counts: u16) -> fmt::Result {
+    for n in 0..=8 {
+        if counts & (1 << n) != 0 {
+            write!(f, "{}", n)?;
+        }
+    }
+    Ok(())
+}
+impl FromStr for LifeLikeRule {
+    type Err = RuleError;
+    // Accepts "B3/S23" and Generations "B2/S/C3" (parts in any order, any
+    // case) as well as the older positional "23/3" and "/2/3" forms.
+    fn from_str(rule: &str) -> Result<LifeLikeRule, RuleError> {
+        let malformed = || RuleError::Malformed(rule.to_string());
+        let parts: Vec<&str> = rule.trim().split('/').collect();
+        if !(2..=3).contains(&parts.len()) {
+            return Err(malformed());
+        }
+        let prefixed = parts
+            .iter()
+            .any(|part| part.starts_with(|c: char| c.is_ascii_alphabetic()));
+        if !prefixed {
+            return Ok(LifeLikeRule {
+                survival: parse_counts(parts[0])?,
+                birth: parse_counts(parts[1])?,
+                states: parts.get(2).map_or(Ok(2), |states| parse_states(states))?,
+            });
+        }
+        let mut birth = None;
+        let mut survival = None;
+        let mut states = None;
+        for part in parts {
+            let mut chars = part.chars();
+            match chars.next().map(|c| c.to_ascii_uppercase()) {
+                Some('B') if birth.is_none() => birth = Some(parse_counts(chars.as_str())?),
+                Some('S') if survival.is_none() => survival = Some(parse_counts(chars.as_str())?),
+                Some('C') | Some('G') if states.is_none() => {
+                    states = Some(parse_states(chars.as_str())?)
+                }
+                _ => return Err(malformed()),
+            }
+        }
+        match (birth, survival) {
+            (Some(birth), Some(survival)) => Ok(LifeLikeRule {
+                birth,
+                survival,
+                states: states.unwrap_or(2),
+            }),
+            _ => Err(malformed()),
+        }
+    }
+}
+impl fmt::Display for LifeLikeRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "B")?;
+        format_counts(f, self.birth)?;
+        write!(f, "/S")?;
+        format_counts(f, self.survival)?;
+        if self.states > 2 {
+            write!(f, "/C{}", self.states)?;
+        }
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RulePreset {
+    Conway = 0,
+    HighLife = 1,
+    Seeds = 2,
+    DayAndNight = 3,
+    LifeWithoutDeath = 4,
+    Maze = 5,
+    Mazectric = 6,
+    Replicator = 7,
+    TwoByTwo = 8,
+    Diamoeba = 9,
+    Morley = 10,
+    Anneal = 11,
+    Coral = 12,
+    LongLife = 13,
+    BriansBrain = 14,
+    StarWars = 15,
+    Bugs = 16,
+    Waffle = 17,
+    Globe = 18,
+    Tlife = 19,
+    JustFriends = 20,
+    Majority = 21,
+}
+impl RulePreset {
+    // in discriminant order, so a preset's position doubles as its JS value
+    pub const ALL: [RulePreset; 22] = [
+        RulePreset::Conway,
+        RulePreset::HighLife,
+        RulePreset::Seeds,
+        RulePreset::DayAndNight,
+        RulePreset::LifeWithoutDeath,
+        RulePreset::Maze,
+        RulePreset::Mazectric,
+        RulePreset::Replicator,
+        RulePreset::TwoByTwo,
+        RulePreset::Diamoeba,
+        RulePreset::Morley,
+        RulePreset::Anneal,
+        RulePreset::Coral,
+        RulePreset::LongLife,
+        RulePreset::BriansBrain,
+        RulePreset::StarWars,
+        RulePreset::Bugs,
+        RulePreset::Waffle,
+        RulePreset::Globe,
+        RulePreset::Tlife,
+        RulePreset::JustFriends,
+        RulePreset::Majority,
+    ];
+    pub fn name(self) -> &'static str {
+        match self {
+            RulePreset::Conway => "Conway's Life",
+            RulePreset::HighLife => "HighLife",
+            RulePreset::Seeds => "Seeds",
+            RulePreset::DayAndNight => "Day & Night",
+            RulePreset::LifeWithoutDeath => "Life without Death",
+            RulePreset::Maze => "Maze",
+            RulePreset::Mazectric => "Mazectric",
+            RulePreset::Replicator => "Replicator",
+            RulePreset::TwoByTwo => "2x2",
+            RulePreset::Diamoeba => "Diamoeba",
+            RulePreset::Morley => "Morley",
+            RulePreset::Anneal => "Anneal",
+            RulePreset::Coral => "Coral",
+            RulePreset::LongLife => "LongLife",
+            RulePreset::BriansBrain => "Brian's Brain",
+            RulePreset::StarWars => "Star Wars",
+            RulePreset::Bugs => "Bugs",
+            RulePreset::Waffle => "Waffle",
+            RulePreset::Globe => "Globe",
+            RulePreset::Tlife => "tlife",
+            RulePreset::JustFriends => "Just Friends",
+            RulePreset::Majority => "Majority vote",
+        }
+    }
+    pub fn rulestring(self) -> &'static str {
+        match self {
+            RulePreset::Conway => "B3/S23",
+            RulePreset::HighLife => "B36/S23",
+            RulePreset::Seeds => "B2/S",
+            RulePreset::DayAndNight => "B3678/S34678",
+            RulePreset::LifeWithoutDeath => "B3/S012345678",
+            RulePreset::Maze => "B3/S12345",
+            RulePreset::Mazectric => "B3/S1234",
+            RulePreset::Replicator => "B1357/S1357",
+            RulePreset::TwoByTwo => "B36/S125",
+            RulePreset::Diamoeba => "B35678/S5678",
+            RulePreset::Morley => "B368/S245",
+            RulePreset::Anneal => "B4678/S35678",
+            RulePreset::Coral => "B3/S45678",
+            RulePreset::LongLife => "B345/S5",
+            RulePreset::BriansBrain => "B2/S/C3",
+            RulePreset::StarWars => "B2/S345/C4",
+            RulePreset::Bugs => "R5,C0,M1,S34..58,B34..45,NM",
+            RulePreset::Waffle => "R7,C0,M1,S100..200,B75..170,NM",
+            RulePreset::Globe => "R8,C0,M0,S163..223,B74..252,NM",
+            RulePreset::Tlife => "B3/S2-i34q",
+            RulePreset::JustFriends => "B2-a/S12",
+            RulePreset::Majority => "Majority",
+        }
+    }
+    pub fn rule(self) -> Rule {
+        self.rulestring()
+            .parse()
+            .expect("preset rulestrings are valid")
+    }
+}
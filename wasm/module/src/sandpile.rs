@@ -0,0 +1,125 @@
+use crate::neighborhood::neighbor_indices;
+use crate::Boundary;
+use wasm_bindgen::prelude::*;
+
+const NEIGHBORS: [(i64, i64); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+const CAPACITY: u32 = 4;
+
+// Abelian sandpile: a cell holding 4 or more grains topples, passing one grain
+// to each orthogonal neighbor. Grains pushed over the edge are lost, so every
+// pile eventually stabilizes with 0-3 grains per cell.
+#[wasm_bindgen]
+pub struct Sandpile {
+    width: u32,
+    height: u32,
+    grains: Vec<u32>,
+    generation: u64,
+}
+#[wasm_bindgen]
+impl Sandpile {
+    fn get_index(&self, row: u32, column: u32) -> usize {
+        (row * self.width + column) as usize
+    }
+    fn neighbors(&self, idx: usize) -> impl Iterator<Item = usize> {
+        let (row, col) = (idx as u32 / self.width, idx as u32 % self.width);
+        neighbor_indices(
+            &NEIGHBORS,
+            Boundary::Dead,
+            row,
+            col,
+            self.width,
+            self.height,
+        )
+    }
+    pub fn new(width: u32, height: u32) -> Sandpile {
+        Sandpile {
+            width,
+            height,
+            grains: vec![0; (width * height) as usize],
+            generation: 0,
+        }
+    }
+    // One synchronous round: every unstable cell topples as many times as its
+    // grains allow.
+    pub fn tick(&mut self) {
+        let mut next = self.grains.clone();
+        for idx in 0..self.grains.len() {
+            let topples = self.grains[idx] / CAPACITY;
+            if topples == 0 {
+                continue;
+            }
+            next[idx] -= topples * CAPACITY;
+            for neighbor in self.neighbors(idx) {
+                next[neighbor] += topples;
+            }
+        }
+        self.grains = next;
+        self.generation += 1;
+    }
+    pub fn tick_n(&mut self, n: u32) {
+        for _ in 0..n {
+            self.tick();
+        }
+    }
+    // Topples until no cell is left unstable and returns how many topplings
+    // that took. The order doesn't matter: the final state is the same.
+    pub fn stabilize(&mut self) -> u64 {
+        let mut topplings = 0;
+        let mut unstable: Vec<usize> = (0..self.grains.len())
+            .filter(|&idx| self.grains[idx] >= CAPACITY)
+            .collect();
+        while let Some(idx) = unstable.pop() {
+            let topples = self.grains[idx] / CAPACITY;
+            if topples == 0 {
+                continue;
+            }
+            self.grains[idx] -= topples * CAPACITY;
+            topplings += topples as u64;
+            for neighbor in self.neighbors(idx) {
+                self.grains[neighbor] += topples;
+                if self.grains[neighbor] >= CAPACITY {
+                    unstable.push(neighbor);
+                }
+            }
+        }
+        topplings
+    }
+    pub fn is_stable(&self) -> bool {
+        self.grains.iter().all(|&grains| grains < CAPACITY)
+    }
+    pub fn drop_grain(&mut self, row: u32, column: u32, n: u32) {
+        let idx = self.get_index(row, column);
+        self.grains[idx] = self.grains[idx].saturating_add(n);
+    }
+    pub fn get_grains(&self, row: u32, column: u32) -> u32 {
+        self.grains[self.get_index(row, column)]
+    }
+    pub fn total_grains(&self) -> u64 {
+        self.grains.iter().map(|&grains| grains as u64).sum()
+    }
+    pub fn clear(&mut self) {
+        self.grains.iter_mut().for_each(|grains| *grains = 0);
+        self.generation = 0;
+    }
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    pub fn grains(&self) -> js_sys::Uint32Array {
+        unsafe { js_sys::Uint32Array::view(&self.grains) }
+    }
+    // Grain counts capped at 255, which is exact once the pile is stable.
+    pub fn cells(&self) -> js_sys::Uint8Array {
+        let cells: Vec<u8> = self
+            .grains
+            .iter()
+            .map(|&grains| grains.min(u8::MAX as u32) as u8)
+            .collect();
+        js_sys::Uint8Array::from(&cells[..])
+    }
+}
@@ -0,0 +1,30 @@
+// The last `limit` values pushed, oldest first, in one contiguous slice that
+// JS can view. Storage runs to twice the limit before the older half is
+// dropped, so pushing stays cheap.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Series {
+    limit: usize,
+    values: Vec<u32>,
+}
+impl Series {
+    pub(crate) fn limit(&self) -> usize {
+        self.limit
+    }
+    pub(crate) fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        let excess = self.values.len().saturating_sub(limit);
+        self.values.drain(..excess);
+    }
+    pub(crate) fn push(&mut self, value: u32) {
+        if self.limit == 0 {
+            return;
+        }
+        if self.values.len() >= 2 * self.limit {
+            self.values.drain(..self.limit);
+        }
+        self.values.push(value);
+    }
+    pub(crate) fn values(&self) -> &[u32] {
+        &self.values[self.values.len().saturating_sub(self.limit)..]
+    }
+}
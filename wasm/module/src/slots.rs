@@ -0,0 +1,95 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{IdbDatabase, IdbObjectStore, IdbRequest, IdbTransactionMode};
+
+// Named slots of save() snapshots in IndexedDB, for grids too big for
+// localStorage. Saving and loading can't borrow a universe across an await,
+// so these take and give the snapshot bytes:
+//
+//     await store_snapshot("glider gun", universe.save());
+//     const universe = Universe.load(await fetch_snapshot("glider gun"));
+
+const DATABASE: &str = "wasm-module";
+const STORE: &str = "snapshots";
+
+// Resolves once `request` succeeds, with its result. The handlers are
+// detached and freed once it settles, as one of them never runs.
+async fn completed(request: &IdbRequest) -> Result<JsValue, JsValue> {
+    let mut handlers = None;
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        let result = request.clone();
+        let on_success = Closure::<dyn FnMut()>::once(move || {
+            let _ = resolve.call1(&JsValue::UNDEFINED, &result.result().unwrap_or_default());
+        });
+        let failed = request.clone();
+        let on_error = Closure::<dyn FnMut()>::once(move || {
+            let error = failed.error().ok().flatten().map(JsValue::from);
+            let _ = reject.call1(&JsValue::UNDEFINED, &error.unwrap_or_default());
+        });
+        request.set_onsuccess(Some(on_success.as_ref().unchecked_ref()));
+        request.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+        handlers = Some((on_success, on_error));
+    });
+    let settled = JsFuture::from(promise).await;
+    request.set_onsuccess(None);
+    request.set_onerror(None);
+    drop(handlers);
+    settled
+}
+async fn open() -> Result<IdbDatabase, JsValue> {
+    let factory = web_sys::window()
+        .and_then(|window| window.indexed_db().ok().flatten())
+        .ok_or_else(|| JsValue::from_str("IndexedDB is not available"))?;
+    let request = factory.open_with_u32(DATABASE, 1)?;
+    let upgrading = request.clone();
+    let on_upgrade = Closure::<dyn FnMut()>::once(move || {
+        if let Ok(database) = upgrading.result() {
+            let _ = database
+                .unchecked_into::<IdbDatabase>()
+                .create_object_store(STORE);
+        }
+    });
+    request.set_onupgradeneeded(Some(on_upgrade.as_ref().unchecked_ref()));
+    let opened = completed(&request).await;
+    request.set_onupgradeneeded(None);
+    drop(on_upgrade);
+    Ok(opened?.unchecked_into())
+}
+// The store in a transaction of its own. The connection is closed straight
+// away, which lets the transaction finish first, so none are left open.
+async fn store(mode: IdbTransactionMode) -> Result<IdbObjectStore, JsValue> {
+    let database = open().await?;
+    let store = database
+        .transaction_with_str_and_mode(STORE, mode)
+        .and_then(|transaction| transaction.object_store(STORE));
+    database.close();
+    store
+}
+
+// Saves `snapshot` under `slot`, replacing whatever was there.
+#[wasm_bindgen]
+pub async fn store_snapshot(slot: String, snapshot: Vec<u8>) -> Result<(), JsValue> {
+    let bytes = js_sys::Uint8Array::from(&snapshot[..]);
+    let store = store(IdbTransactionMode::Readwrite).await?;
+    completed(&store.put_with_key(&bytes, &JsValue::from_str(&slot))?).await?;
+    Ok(())
+}
+// The snapshot saved under `slot`, or undefined.
+#[wasm_bindgen]
+pub async fn fetch_snapshot(slot: String) -> Result<JsValue, JsValue> {
+    let store = store(IdbTransactionMode::Readonly).await?;
+    completed(&store.get(&JsValue::from_str(&slot))?).await
+}
+#[wasm_bindgen]
+pub async fn delete_snapshot(slot: String) -> Result<(), JsValue> {
+    let store = store(IdbTransactionMode::Readwrite).await?;
+    completed(&store.delete(&JsValue::from_str(&slot))?).await?;
+    Ok(())
+}
+// Array of every slot name, in sorted order.
+#[wasm_bindgen]
+pub async fn list_snapshots() -> Result<JsValue, JsValue> {
+    let store = store(IdbTransactionMode::Readonly).await?;
+    completed(&store.get_all_keys()?).await
+}
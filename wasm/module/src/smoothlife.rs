@@ -0,0 +1,232 @@
+use crate::rng::Rng;
+use crate::Boundary;
+use wasm_bindgen::prelude::*;
+
+fn sigmoid(x: f32, a: f32, alpha: f32) -> f32 {
+    1.0 / (1.0 + (-(x - a) * 4.0 / alpha).exp())
+}
+// smooth step from 0 to 1 between a and b
+fn sigmoid_interval(x: f32, a: f32, b: f32, alpha: f32) -> f32 {
+    sigmoid(x, a, alpha) * (1.0 - sigmoid(x, b, alpha))
+}
+fn sigmoid_mix(x: f32, y: f32, m: f32, alpha: f32) -> f32 {
+    x * (1.0 - sigmoid(m, 0.5, alpha)) + y * sigmoid(m, 0.5, alpha)
+}
+// Anti-aliased (delta_row, delta_col, weight) samples of the ring between
+// `inner` and `outer` radii, normalized to sum to 1.
+fn ring_samples(inner: f32, outer: f32) -> Vec<(i64, i64, f32)> {
+    let reach = outer.ceil() as i64 + 1;
+    let mut samples = Vec::new();
+    for delta_row in -reach..=reach {
+        for delta_col in -reach..=reach {
+            let distance = ((delta_row * delta_row + delta_col * delta_col) as f32).sqrt();
+            let coverage = (outer + 0.5 - distance).clamp(0.0, 1.0);
+            let hole = if inner > 0.0 {
+                (inner + 0.5 - distance).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let weight = coverage - hole;
+            if weight > 0.0 {
+                samples.push((delta_row, delta_col, weight));
+            }
+        }
+    }
+    let total: f32 = samples.iter().map(|&(_, _, weight)| weight).sum();
+    samples
+        .iter_mut()
+        .for_each(|(_, _, weight)| *weight /= total);
+    samples
+}
+
+// Rafler's SmoothLife: the continuous analogue of Life, where a cell's inner
+// disk filling `m` decides whether the outer ring filling `n` falls in the
+// birth interval [b1, b2] or the survival interval [d1, d2]. With `dt` below
+// 1 the state eases towards the transition value instead of jumping to it.
+#[wasm_bindgen]
+pub struct SmoothLife {
+    width: u32,
+    height: u32,
+    field: Vec<f32>,
+    // field mapped to 0-255, refreshed every tick
+    cells: Vec<u8>,
+    outer_radius: f32,
+    birth: (f32, f32),
+    survival: (f32, f32),
+    alpha_n: f32,
+    alpha_m: f32,
+    dt: f32,
+    inner: Vec<(i64, i64, f32)>,
+    outer: Vec<(i64, i64, f32)>,
+    boundary: Boundary,
+    generation: u64,
+}
+#[wasm_bindgen]
+impl SmoothLife {
+    fn get_index(&self, row: u32, column: u32) -> usize {
+        (row * self.width + column) as usize
+    }
+    fn rebuild_kernels(&mut self) {
+        // Rafler's ratio between the disk and the ring around it
+        let inner_radius = self.outer_radius / 3.0;
+        self.inner = ring_samples(0.0, inner_radius);
+        self.outer = ring_samples(inner_radius, self.outer_radius);
+    }
+    fn filling(&self, samples: &[(i64, i64, f32)], row: u32, column: u32) -> f32 {
+        let mut sum = 0.0;
+        for &(delta_row, delta_col, weight) in samples {
+            if let (Some(row), Some(col)) = (
+                self.boundary.resolve(row as i64 + delta_row, self.height),
+                self.boundary.resolve(column as i64 + delta_col, self.width),
+            ) {
+                sum += weight * self.field[self.get_index(row, col)];
+            }
+        }
+        sum
+    }
+    fn transition(&self, n: f32, m: f32) -> f32 {
+        let low = sigmoid_mix(self.birth.0, self.survival.0, m, self.alpha_m);
+        let high = sigmoid_mix(self.birth.1, self.survival.1, m, self.alpha_m);
+        sigmoid_interval(n, low, high, self.alpha_n)
+    }
+    fn refresh_cells(&mut self) {
+        for (cell, &value) in self.cells.iter_mut().zip(self.field.iter()) {
+            *cell = (value * 255.0) as u8;
+        }
+    }
+    pub fn new(width: u32, height: u32) -> SmoothLife {
+        let size = (width * height) as usize;
+        let mut smooth_life = SmoothLife {
+            width,
+            height,
+            field: vec![0.0; size],
+            cells: vec![0; size],
+            outer_radius: 12.0,
+            birth: (0.278, 0.365),
+            survival: (0.267, 0.445),
+            alpha_n: 0.028,
+            alpha_m: 0.147,
+            dt: 1.0,
+            inner: Vec::new(),
+            outer: Vec::new(),
+            boundary: Boundary::default(),
+            generation: 0,
+        };
+        smooth_life.rebuild_kernels();
+        smooth_life.randomize(0);
+        smooth_life
+    }
+    pub fn tick(&mut self) {
+        let mut next = self.field.clone();
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let idx = self.get_index(row, col);
+                let m = self.filling(&self.inner, row, col);
+                let n = self.filling(&self.outer, row, col);
+                let target = self.transition(n, m);
+                let value = self.field[idx];
+                next[idx] = (value + self.dt * (target - value)).clamp(0.0, 1.0);
+            }
+        }
+        self.field = next;
+        self.refresh_cells();
+        self.generation += 1;
+    }
+    pub fn tick_n(&mut self, n: u32) {
+        for _ in 0..n {
+            self.tick();
+        }
+    }
+    // Scatters filled disks the size of the inner disk over an empty world,
+    // dense enough that neighboring speckles overlap into live ones.
+    pub fn randomize(&mut self, seed: u64) {
+        self.field.iter_mut().for_each(|value| *value = 0.0);
+        let mut rng = Rng::new(seed);
+        if self.width > 0 && self.height > 0 {
+            let radius = self.outer_radius / 3.0;
+            let area = (self.width * self.height) as f32;
+            let count = (area / (radius * radius * 6.0)) as u32 + 1;
+            for _ in 0..count {
+                let row = (rng.next_u64() % self.height as u64) as i64;
+                let col = (rng.next_u64() % self.width as u64) as i64;
+                let r = radius.ceil() as i64;
+                for delta_row in -r..=r {
+                    for delta_col in -r..=r {
+                        if ((delta_row * delta_row + delta_col * delta_col) as f32)
+                            > radius * radius
+                        {
+                            continue;
+                        }
+                        if let (Some(row), Some(col)) = (
+                            self.boundary.resolve(row + delta_row, self.height),
+                            self.boundary.resolve(col + delta_col, self.width),
+                        ) {
+                            let idx = self.get_index(row, col);
+                            self.field[idx] = 1.0;
+                        }
+                    }
+                }
+            }
+        }
+        self.refresh_cells();
+        self.generation = 0;
+    }
+    pub fn clear(&mut self) {
+        self.field.iter_mut().for_each(|value| *value = 0.0);
+        self.refresh_cells();
+        self.generation = 0;
+    }
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    pub fn outer_radius(&self) -> f32 {
+        self.outer_radius
+    }
+    pub fn set_outer_radius(&mut self, radius: f32) {
+        self.outer_radius = radius.max(3.0);
+        self.rebuild_kernels();
+    }
+    pub fn set_birth(&mut self, b1: f32, b2: f32) {
+        self.birth = (b1, b2);
+    }
+    pub fn set_survival(&mut self, d1: f32, d2: f32) {
+        self.survival = (d1, d2);
+    }
+    // sigmoid widths of the ring and disk fillings
+    pub fn set_sigmoid_widths(&mut self, alpha_n: f32, alpha_m: f32) {
+        self.alpha_n = alpha_n;
+        self.alpha_m = alpha_m;
+    }
+    pub fn dt(&self) -> f32 {
+        self.dt
+    }
+    pub fn set_dt(&mut self, dt: f32) {
+        self.dt = dt.clamp(0.0, 1.0);
+    }
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+    }
+    pub fn get_value(&self, row: u32, column: u32) -> f32 {
+        self.field[self.get_index(row, column)]
+    }
+    pub fn set_value(&mut self, row: u32, column: u32, value: f32) {
+        let idx = self.get_index(row, column);
+        self.field[idx] = value.clamp(0.0, 1.0);
+        self.cells[idx] = (self.field[idx] * 255.0) as u8;
+    }
+    pub fn field(&self) -> js_sys::Float32Array {
+        unsafe { js_sys::Float32Array::view(&self.field) }
+    }
+    pub fn cells(&self) -> js_sys::Uint8Array {
+        unsafe { js_sys::Uint8Array::view(&self.cells) }
+    }
+}
@@ -0,0 +1,288 @@
+use crate::neighborhood::MAX_RADIUS;
+use crate::rule::RuleError;
+use crate::{Boundary, ColorVariant, Neighborhood, Rule, TieBreak};
+use std::error::Error;
+use std::fmt;
+
+const MAGIC: &[u8; 4] = b"LIFE";
+// Every version ever written stays readable:
+// 1. cells and colors as runs
+// 2. a cell encoding byte ahead of the cells, so two-state grids can be
+//    stored a bit per cell
+const VERSION: u8 = 2;
+const RUNS: u8 = 0;
+const BITS: u8 = 1;
+// Most cells a snapshot may claim, a universe already several hundred MB
+// with everything kept per cell. Snapshots come from URLs and files, so a
+// few bytes of header mustn't be able to ask for more.
+pub(crate) const MAX_CELLS: u64 = 1 << 26;
+
+// Everything save() keeps of a universe. Integers are little-endian, the
+// rule is its rulestring, and colors, and cells unless they are bit-packed,
+// are runs of a varint count followed by the value repeated.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Snapshot {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) generation: u64,
+    pub(crate) rule: Rule,
+    pub(crate) neighborhood: Neighborhood,
+    pub(crate) radius: u32,
+    pub(crate) boundary: Boundary,
+    pub(crate) tie_break: TieBreak,
+    pub(crate) color_variant: ColorVariant,
+    pub(crate) cells: Vec<u8>,
+    pub(crate) colors: Vec<u8>,
+}
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SnapshotError {
+    NotASnapshot,
+    NotAReplayLog,
+    NotAPatch,
+    SizeMismatch,
+    UnsupportedVersion(u8),
+    Truncated,
+    InvalidField(&'static str),
+    InvalidRule(RuleError),
+}
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SnapshotError::NotASnapshot => write!(f, "not a universe snapshot"),
+            SnapshotError::NotAReplayLog => write!(f, "not a replay log"),
+            SnapshotError::NotAPatch => write!(f, "not a universe patch"),
+            SnapshotError::SizeMismatch => write!(f, "universes differ in size"),
+            SnapshotError::UnsupportedVersion(version) => {
+                write!(f, "unsupported snapshot version {}", version)
+            }
+            SnapshotError::Truncated => write!(f, "snapshot is truncated"),
+            SnapshotError::InvalidField(field) => write!(f, "invalid snapshot {}", field),
+            SnapshotError::InvalidRule(error) => write!(f, "invalid snapshot rule: {}", error),
+        }
+    }
+}
+impl Error for SnapshotError {}
+impl From<RuleError> for SnapshotError {
+    fn from(error: RuleError) -> Self {
+        SnapshotError::InvalidRule(error)
+    }
+}
+
+pub(crate) fn push_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+pub(crate) fn push_string(bytes: &mut Vec<u8>, text: &str) {
+    push_varint(bytes, text.len() as u64);
+    bytes.extend_from_slice(text.as_bytes());
+}
+fn push_runs(bytes: &mut Vec<u8>, values: &[u8]) {
+    let mut start = 0;
+    while start < values.len() {
+        let value = values[start];
+        let run = values[start..].iter().take_while(|&&v| v == value).count();
+        push_varint(bytes, run as u64);
+        bytes.push(value);
+        start += run;
+    }
+}
+
+// One bit per cell, lowest bit first, or None if some cell is in a state
+// above 1.
+fn pack_bits(cells: &[u8]) -> Option<Vec<u8>> {
+    let mut bits = vec![0u8; cells.len().div_ceil(8)];
+    for (idx, &state) in cells.iter().enumerate() {
+        match state {
+            0 => {}
+            1 => bits[idx / 8] |= 1 << (idx % 8),
+            _ => return None,
+        }
+    }
+    Some(bits)
+}
+fn unpack_bits(bits: &[u8], len: usize) -> Vec<u8> {
+    (0..len).map(|idx| bits[idx / 8] >> (idx % 8) & 1).collect()
+}
+
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+}
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Reader<'a> {
+        Reader { bytes }
+    }
+    pub(crate) fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+    pub(crate) fn take(&mut self, n: usize) -> Result<&'a [u8], SnapshotError> {
+        if self.bytes.len() < n {
+            return Err(SnapshotError::Truncated);
+        }
+        let (taken, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+        Ok(taken)
+    }
+    pub(crate) fn byte(&mut self) -> Result<u8, SnapshotError> {
+        Ok(self.take(1)?[0])
+    }
+    pub(crate) fn u32(&mut self) -> Result<u32, SnapshotError> {
+        let mut le = [0; 4];
+        le.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(le))
+    }
+    pub(crate) fn u64(&mut self) -> Result<u64, SnapshotError> {
+        let mut le = [0; 8];
+        le.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(le))
+    }
+    pub(crate) fn varint(&mut self) -> Result<u64, SnapshotError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(SnapshotError::InvalidField("run length"))
+    }
+    pub(crate) fn f32(&mut self) -> Result<f32, SnapshotError> {
+        Ok(f32::from_bits(self.u32()?))
+    }
+    pub(crate) fn string(&mut self, field: &'static str) -> Result<&'a str, SnapshotError> {
+        let len = self.varint()? as usize;
+        std::str::from_utf8(self.take(len)?).map_err(|_| SnapshotError::InvalidField(field))
+    }
+    pub(crate) fn rule(&mut self) -> Result<Rule, SnapshotError> {
+        Ok(self.string("rule")?.parse()?)
+    }
+    pub(crate) fn neighborhood(&mut self) -> Result<Neighborhood, SnapshotError> {
+        match self.byte()? {
+            0 => Ok(Neighborhood::Moore),
+            1 => Ok(Neighborhood::VonNeumann),
+            2 => Ok(Neighborhood::Hexagonal),
+            _ => Err(SnapshotError::InvalidField("neighborhood")),
+        }
+    }
+    pub(crate) fn boundary(&mut self) -> Result<Boundary, SnapshotError> {
+        match self.byte()? {
+            0 => Ok(Boundary::Wrap),
+            1 => Ok(Boundary::Dead),
+            2 => Ok(Boundary::Mirror),
+            _ => Err(SnapshotError::InvalidField("boundary")),
+        }
+    }
+    pub(crate) fn tie_break(&mut self) -> Result<TieBreak, SnapshotError> {
+        match self.byte()? {
+            0 => Ok(TieBreak::Keep),
+            1 => Ok(TieBreak::Alive),
+            2 => Ok(TieBreak::Dead),
+            3 => Ok(TieBreak::Random),
+            _ => Err(SnapshotError::InvalidField("tie break")),
+        }
+    }
+    pub(crate) fn color_variant(&mut self) -> Result<ColorVariant, SnapshotError> {
+        match self.byte()? {
+            0 => Ok(ColorVariant::Off),
+            1 => Ok(ColorVariant::Immigration),
+            2 => Ok(ColorVariant::QuadLife),
+            _ => Err(SnapshotError::InvalidField("color variant")),
+        }
+    }
+    fn runs(&mut self, len: usize) -> Result<Vec<u8>, SnapshotError> {
+        // grown run by run, so memory follows what the runs actually hold
+        let mut values = Vec::new();
+        while values.len() < len {
+            let run = self.varint()?;
+            if run == 0 || run > (len - values.len()) as u64 {
+                return Err(SnapshotError::InvalidField("run length"));
+            }
+            let value = self.byte()?;
+            values.resize(values.len() + run as usize, value);
+        }
+        Ok(values)
+    }
+}
+
+impl Snapshot {
+    pub(crate) fn is_snapshot(bytes: &[u8]) -> bool {
+        bytes.starts_with(MAGIC)
+    }
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.width.to_le_bytes());
+        bytes.extend_from_slice(&self.height.to_le_bytes());
+        bytes.extend_from_slice(&self.generation.to_le_bytes());
+        push_string(&mut bytes, &self.rule.to_string());
+        bytes.push(self.neighborhood as u8);
+        bytes.extend_from_slice(&self.radius.to_le_bytes());
+        bytes.push(self.boundary as u8);
+        bytes.push(self.tie_break as u8);
+        bytes.push(self.color_variant as u8);
+        let mut runs = Vec::new();
+        push_runs(&mut runs, &self.cells);
+        let bits = pack_bits(&self.cells).filter(|bits| bits.len() < runs.len());
+        match bits {
+            Some(bits) => {
+                bytes.push(BITS);
+                bytes.extend_from_slice(&bits);
+            }
+            None => {
+                bytes.push(RUNS);
+                bytes.extend_from_slice(&runs);
+            }
+        }
+        push_runs(&mut bytes, &self.colors);
+        bytes
+    }
+    pub(crate) fn decode(bytes: &[u8]) -> Result<Snapshot, SnapshotError> {
+        let mut reader = Reader::new(bytes);
+        if reader.take(MAGIC.len()).ok() != Some(&MAGIC[..]) {
+            return Err(SnapshotError::NotASnapshot);
+        }
+        let version = reader.byte()?;
+        if !(1..=VERSION).contains(&version) {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+        let width = reader.u32()?;
+        let height = reader.u32()?;
+        if width as u64 * height as u64 > MAX_CELLS {
+            return Err(SnapshotError::InvalidField("dimensions"));
+        }
+        let len = (width * height) as usize;
+        let generation = reader.u64()?;
+        let rule = reader.rule()?;
+        let neighborhood = reader.neighborhood()?;
+        let radius = reader.u32()?;
+        if !(1..=MAX_RADIUS).contains(&radius) {
+            return Err(SnapshotError::InvalidField("radius"));
+        }
+        let boundary = reader.boundary()?;
+        let tie_break = reader.tie_break()?;
+        let color_variant = reader.color_variant()?;
+        let encoding = if version >= 2 { reader.byte()? } else { RUNS };
+        let cells = match encoding {
+            RUNS => reader.runs(len)?,
+            BITS => unpack_bits(reader.take(len.div_ceil(8))?, len),
+            _ => return Err(SnapshotError::InvalidField("cell encoding")),
+        };
+        let colors = reader.runs(len)?;
+        Ok(Snapshot {
+            width,
+            height,
+            generation,
+            rule,
+            neighborhood,
+            radius,
+            boundary,
+            tie_break,
+            color_variant,
+            cells,
+            colors,
+        })
+    }
+}
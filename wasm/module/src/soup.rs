@@ -0,0 +1,82 @@
+use wasm_bindgen::prelude::*;
+
+// Symmetries of apgsearch-style soups, named after their symmetry groups.
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Symmetry {
+    // no symmetry at all
+    #[default]
+    C1 = 0,
+    // unchanged by a half turn
+    C2 = 1,
+    // mirrored left to right
+    D2 = 2,
+    // mirrored left to right and top to bottom
+    D4 = 3,
+    // every rotation and reflection of the square
+    D8 = 4,
+}
+impl Symmetry {
+    // The cells of a `size` x `size` square that the symmetry forces to match
+    // (row, column), including itself.
+    pub(crate) fn orbit(self, size: u32, row: u32, col: u32) -> Vec<(u32, u32)> {
+        let (flip_row, flip_col) = (size - 1 - row, size - 1 - col);
+        let mut orbit = match self {
+            Symmetry::C1 => vec![(row, col)],
+            Symmetry::C2 => vec![(row, col), (flip_row, flip_col)],
+            Symmetry::D2 => vec![(row, col), (row, flip_col)],
+            Symmetry::D4 => vec![
+                (row, col),
+                (row, flip_col),
+                (flip_row, col),
+                (flip_row, flip_col),
+            ],
+            Symmetry::D8 => vec![
+                (row, col),
+                (row, flip_col),
+                (flip_row, col),
+                (flip_row, flip_col),
+                (col, row),
+                (col, flip_row),
+                (flip_col, row),
+                (flip_col, flip_row),
+            ],
+        };
+        orbit.sort_unstable();
+        orbit.dedup();
+        orbit
+    }
+}
+
+// How a soup run to stabilization ended, from Universe::run_soup().
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct SoupResult {
+    pub(crate) seed: u64,
+    pub(crate) lifespan: u64,
+    pub(crate) population: u32,
+    pub(crate) period: Option<u32>,
+}
+#[wasm_bindgen]
+impl SoupResult {
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+    // Generations until the soup first reached the cycle it ended in, or
+    // the generation cap if it never settled.
+    pub fn lifespan(&self) -> u64 {
+        self.lifespan
+    }
+    // population when the run stopped
+    pub fn final_population(&self) -> u32 {
+        self.population
+    }
+    // period of the cycle it settled into, None if it didn't
+    pub fn period(&self) -> Option<u32> {
+        self.period
+    }
+    pub fn stabilized(&self) -> bool {
+        self.period.is_some()
+    }
+}
@@ -0,0 +1,118 @@
+use crate::apgcode;
+use crate::pattern::{self, Pattern, Transform};
+use crate::plane::Plane;
+use crate::snapshot::MAX_CELLS;
+use crate::{Cell, Rule};
+use wasm_bindgen::prelude::*;
+
+// Life on an unbounded plane that stores only its live cells, for patterns
+// spread over far more cells than a dense Universe could hold. It runs the
+// same two-state radius 1 Moore rules apgcode() does.
+#[wasm_bindgen]
+pub struct SparseUniverse {
+    plane: Plane,
+    generation: u64,
+}
+#[wasm_bindgen]
+impl SparseUniverse {
+    pub fn new(rule: &str) -> Result<SparseUniverse, JsError> {
+        let rule: Rule = rule.parse()?;
+        Ok(SparseUniverse {
+            plane: Plane::new(&rule, &[])?,
+            generation: 0,
+        })
+    }
+    pub fn tick(&mut self) {
+        self.plane.step();
+        self.generation += 1;
+    }
+    pub fn tick_n(&mut self, n: u32) {
+        for _ in 0..n {
+            self.tick();
+        }
+    }
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    pub fn rule(&self) -> String {
+        self.plane.rule().to_string()
+    }
+    pub fn population(&self) -> u32 {
+        self.plane.len() as u32
+    }
+    pub fn get_cell(&self, row: i32, column: i32) -> Cell {
+        if self.plane.is_alive(row as i64, column as i64) {
+            Cell::Alive
+        } else {
+            Cell::Dead
+        }
+    }
+    pub fn set_cell(&mut self, row: i32, column: i32, cell: Cell) {
+        self.plane
+            .set(row as i64, column as i64, cell == Cell::Alive);
+    }
+    pub fn clear(&mut self) {
+        self.plane.clear();
+        self.generation = 0;
+    }
+    // [min_row, min_column, max_row, max_column] of the live cells, or None
+    // when there are none.
+    pub fn bounding_box(&self) -> Option<Vec<i32>> {
+        let cells = self.plane.cells();
+        let (top, bottom) = (cells.first()?.0, cells.last()?.0);
+        let left = cells.iter().map(|&(_, col)| col).min()?;
+        let right = cells.iter().map(|&(_, col)| col).max()?;
+        Some(vec![top as i32, left as i32, bottom as i32, right as i32])
+    }
+    // Interleaved row, column pairs of every live cell, in row-major order.
+    pub fn live_cells(&self) -> Vec<i32> {
+        self.plane
+            .cells()
+            .into_iter()
+            .flat_map(|(row, col)| [row as i32, col as i32])
+            .collect()
+    }
+    // The `width` x `height` window with its top-left corner at (row,
+    // column), as one Cell per byte in row-major order, for drawing the part
+    // of the plane in view.
+    pub fn window(&self, row: i32, column: i32, width: u32, height: u32) -> Vec<u8> {
+        let mut cells = vec![Cell::Dead as u8; (width * height) as usize];
+        for (cell_row, cell_col) in self.plane.cells() {
+            let (r, c) = (cell_row - row as i64, cell_col - column as i64);
+            if (0..height as i64).contains(&r) && (0..width as i64).contains(&c) {
+                cells[(r * width as i64 + c) as usize] = Cell::Alive as u8;
+            }
+        }
+        cells
+    }
+    // Sets the live cells of a transformed RLE pattern with its top-left
+    // corner at (row, column).
+    pub fn paste_rle(
+        &mut self,
+        rle: &str,
+        row: i32,
+        column: i32,
+        transform: Transform,
+    ) -> Result<(), JsError> {
+        let pattern = pattern::parse_rle(rle, MAX_CELLS as usize)?.transformed(transform);
+        for (cell_row, cell_col) in apgcode::live_cells(&pattern) {
+            self.plane
+                .set(row as i64 + cell_row, column as i64 + cell_col, true);
+        }
+        Ok(())
+    }
+    // RLE of the live cells, cropped to their bounding box.
+    pub fn to_rle(&self) -> String {
+        let coordinates: Vec<(i64, i64, u8)> = self
+            .plane
+            .cells()
+            .into_iter()
+            .map(|(row, col)| (row, col, Cell::Alive as u8))
+            .collect();
+        pattern::format_rle(
+            &Pattern::from_coordinates(&coordinates),
+            &self.rule(),
+            false,
+        )
+    }
+}
@@ -0,0 +1,98 @@
+use crate::components;
+use crate::{Boundary, Cell, Neighborhood};
+use wasm_bindgen::prelude::*;
+
+// Measures of one generation, from Universe::stats().
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct Stats {
+    generation: u64,
+    population: u32,
+    density: f64,
+    entropy: f64,
+    cluster_sizes: Vec<u32>,
+}
+#[wasm_bindgen]
+impl Stats {
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    pub fn population(&self) -> u32 {
+        self.population
+    }
+    // live cells per cell of the grid
+    pub fn density(&self) -> f64 {
+        self.density
+    }
+    // Shannon entropy, in bits from 0 to 4, of the live and dead cells seen
+    // through every 2 x 2 window that fits on the grid
+    pub fn entropy(&self) -> f64 {
+        self.entropy
+    }
+    pub fn cluster_count(&self) -> u32 {
+        self.cluster_sizes.len() as u32
+    }
+    pub fn largest_cluster(&self) -> u32 {
+        self.cluster_sizes.first().copied().unwrap_or(0)
+    }
+    pub fn mean_cluster_size(&self) -> f64 {
+        match self.cluster_sizes.len() {
+            0 => 0.0,
+            count => self.population as f64 / count as f64,
+        }
+    }
+    // live cells in each 8-connected cluster, largest first
+    pub fn cluster_sizes(&self) -> Vec<u32> {
+        self.cluster_sizes.clone()
+    }
+}
+
+fn block_entropy(cells: &[u8], width: u32, height: u32) -> f64 {
+    let alive =
+        |row: u32, col: u32| (cells[(row * width + col) as usize] == Cell::Alive as u8) as usize;
+    let mut counts = [0u32; 16];
+    for row in 1..height {
+        for col in 1..width {
+            let block = alive(row - 1, col - 1)
+                | alive(row - 1, col) << 1
+                | alive(row, col - 1) << 2
+                | alive(row, col) << 3;
+            counts[block] += 1;
+        }
+    }
+    let total: u32 = counts.iter().sum();
+    counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / total as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+pub(crate) fn measure(
+    cells: &[u8],
+    width: u32,
+    height: u32,
+    boundary: Boundary,
+    generation: u64,
+    population: u32,
+) -> Stats {
+    let (labels, count) = components::label(cells, width, height, boundary, Neighborhood::Moore, 1);
+    let mut cluster_sizes = vec![0; count as usize];
+    for &label in labels.iter().filter(|&&label| label > 0) {
+        cluster_sizes[label as usize - 1] += 1;
+    }
+    cluster_sizes.sort_unstable_by(|a, b| b.cmp(a));
+    Stats {
+        generation,
+        population,
+        density: match cells.len() {
+            0 => 0.0,
+            len => population as f64 / len as f64,
+        },
+        entropy: block_entropy(cells, width, height),
+        cluster_sizes,
+    }
+}
@@ -0,0 +1,96 @@
+use crate::Boundary;
+use std::ops::Range;
+
+// side of a tile, in cells
+const TILE: u32 = 32;
+
+// The grid cut into TILE x TILE tiles, with the ones a tick has to look at
+// marked awake. A tile sleeps once no cell within reach of it changed in the
+// last generation, as then none of its cells can change in the next one
+// under a rule without randomness.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Tiles {
+    width: u32,
+    height: u32,
+    columns: u32,
+    awake: Vec<bool>,
+    awake_count: usize,
+}
+impl Tiles {
+    pub(crate) fn new(width: u32, height: u32) -> Tiles {
+        let columns = width.div_ceil(TILE);
+        let count = (columns * height.div_ceil(TILE)) as usize;
+        Tiles {
+            width,
+            height,
+            columns,
+            awake: vec![true; count],
+            awake_count: count,
+        }
+    }
+    pub(crate) fn wake_all(&mut self) {
+        if self.awake_count == self.awake.len() {
+            return;
+        }
+        self.awake.iter_mut().for_each(|awake| *awake = true);
+        self.awake_count = self.awake.len();
+    }
+    // Whether enough tiles are awake that stepping the whole grid in one go
+    // beats stepping them one by one.
+    pub(crate) fn mostly_awake(&self) -> bool {
+        self.awake_count * 8 > self.awake.len()
+    }
+    // Puts every tile to sleep except those within `radius` cells of a cell
+    // in `changed`.
+    pub(crate) fn settle(&mut self, changed: &[u32], radius: u32, boundary: Boundary) {
+        self.awake.iter_mut().for_each(|awake| *awake = false);
+        self.awake_count = 0;
+        let (mut tile_rows, mut tile_cols) = (Vec::new(), Vec::new());
+        for &idx in changed {
+            let (row, col) = (idx / self.width, idx % self.width);
+            reach(&mut tile_rows, row, radius, self.height, boundary);
+            reach(&mut tile_cols, col, radius, self.width, boundary);
+            for &tile_row in tile_rows.iter() {
+                for &tile_col in tile_cols.iter() {
+                    let tile = (tile_row * self.columns + tile_col) as usize;
+                    if !self.awake[tile] {
+                        self.awake[tile] = true;
+                        self.awake_count += 1;
+                    }
+                }
+            }
+        }
+    }
+    // Rows and columns of the cells in each awake tile.
+    pub(crate) fn awake(&self) -> impl Iterator<Item = (Range<u32>, Range<u32>)> + '_ {
+        self.awake
+            .iter()
+            .enumerate()
+            .filter(|(_, &awake)| awake)
+            .map(move |(tile, _)| {
+                let (top, left) = (
+                    tile as u32 / self.columns * TILE,
+                    tile as u32 % self.columns * TILE,
+                );
+                (
+                    top..(top + TILE).min(self.height),
+                    left..(left + TILE).min(self.width),
+                )
+            })
+    }
+}
+
+// Fills `tiles` with the tiles along one axis that hold a coordinate within
+// `radius` of `coord`.
+fn reach(tiles: &mut Vec<u32>, coord: u32, radius: u32, size: u32, boundary: Boundary) {
+    tiles.clear();
+    let radius = radius as i64;
+    for delta in -radius..=radius {
+        if let Some(coord) = boundary.resolve(coord as i64 + delta, size) {
+            let tile = coord / TILE;
+            if !tiles.contains(&tile) {
+                tiles.push(tile);
+            }
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use std::collections::VecDeque;
+
+// A cell as it was before a tick changed its state.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Before {
+    pub(crate) index: u32,
+    pub(crate) state: u8,
+    pub(crate) color: u8,
+    pub(crate) age: u16,
+}
+// The last `limit` ticks, each as the cells it changed, for rewind().
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Timeline {
+    limit: usize,
+    ticks: VecDeque<Vec<Before>>,
+}
+impl Timeline {
+    pub(crate) fn limit(&self) -> usize {
+        self.limit
+    }
+    pub(crate) fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        while self.ticks.len() > limit {
+            self.ticks.pop_front();
+        }
+    }
+    pub(crate) fn len(&self) -> usize {
+        self.ticks.len()
+    }
+    pub(crate) fn push(&mut self, changed: Vec<Before>) {
+        if self.limit == 0 {
+            return;
+        }
+        if self.ticks.len() == self.limit {
+            self.ticks.pop_front();
+        }
+        self.ticks.push_back(changed);
+    }
+    pub(crate) fn pop(&mut self) -> Option<Vec<Before>> {
+        self.ticks.pop_back()
+    }
+    pub(crate) fn clear(&mut self) {
+        self.ticks.clear();
+    }
+}
@@ -0,0 +1,304 @@
+use crate::Boundary;
+use std::error::Error;
+use std::fmt;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    North = 0,
+    East = 1,
+    South = 2,
+    West = 3,
+}
+impl Direction {
+    // `quarter_turns` clockwise
+    fn turn(self, quarter_turns: u8) -> Direction {
+        match (self as u8 + quarter_turns) % 4 {
+            0 => Direction::North,
+            1 => Direction::East,
+            2 => Direction::South,
+            _ => Direction::West,
+        }
+    }
+    fn delta(self) -> (i64, i64) {
+        match self {
+            Direction::North => (-1, 0),
+            Direction::East => (0, 1),
+            Direction::South => (1, 0),
+            Direction::West => (0, -1),
+        }
+    }
+}
+#[derive(Clone, Copy, Debug)]
+struct Transition {
+    write: u8,
+    // clockwise quarter turns: 0 straight on, 1 right, 2 U-turn, 3 left
+    turn: u8,
+    next_state: u8,
+}
+#[derive(Clone, Copy, Debug)]
+struct Ant {
+    row: u32,
+    column: u32,
+    direction: Direction,
+    state: u8,
+}
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TurmiteError {
+    InvalidTurn(char),
+    InvalidTable(String),
+}
+impl fmt::Display for TurmiteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TurmiteError::InvalidTurn(c) => write!(f, "invalid turn {:?}", c),
+            TurmiteError::InvalidTable(reason) => write!(f, "invalid turmite table: {}", reason),
+        }
+    }
+}
+impl Error for TurmiteError {}
+
+fn parse_ant_rule(rule: &str) -> Result<(u8, Vec<Transition>), TurmiteError> {
+    let turns = rule
+        .trim()
+        .chars()
+        .map(|c| match c.to_ascii_uppercase() {
+            'N' => Ok(0),
+            'R' => Ok(1),
+            'U' => Ok(2),
+            'L' => Ok(3),
+            _ => Err(TurmiteError::InvalidTurn(c)),
+        })
+        .collect::<Result<Vec<u8>, _>>()?;
+    if !(2..=255).contains(&turns.len()) {
+        return Err(TurmiteError::InvalidTable(
+            "an ant rule needs 2 to 255 colors".to_string(),
+        ));
+    }
+    let colors = turns.len() as u8;
+    let table = turns
+        .iter()
+        .enumerate()
+        .map(|(color, &turn)| Transition {
+            write: ((color + 1) % colors as usize) as u8,
+            turn,
+            next_state: 0,
+        })
+        .collect();
+    Ok((colors, table))
+}
+fn parse_table(states: u8, colors: u8, table: &[u8]) -> Result<Vec<Transition>, TurmiteError> {
+    let invalid = |reason: &str| Err(TurmiteError::InvalidTable(reason.to_string()));
+    if states == 0 || colors < 2 {
+        return invalid("a turmite needs a state and at least 2 colors");
+    }
+    if table.len() != states as usize * colors as usize * 3 {
+        return invalid("expected 3 entries per state and color");
+    }
+    let table: Vec<Transition> = table
+        .chunks(3)
+        .map(|entry| Transition {
+            write: entry[0],
+            turn: entry[1] % 4,
+            next_state: entry[2],
+        })
+        .collect();
+    if table
+        .iter()
+        .any(|t| t.write >= colors || t.next_state >= states)
+    {
+        return invalid("entry refers to a missing color or state");
+    }
+    Ok(table)
+}
+
+// Grid of colors walked by any number of turmites. Each tick every ant reads
+// the color under it, and its (state, color) entry in the transition table
+// says what color to write, how to turn and which state to move on to,
+// before it steps forward one cell.
+#[wasm_bindgen]
+pub struct Turmites {
+    width: u32,
+    height: u32,
+    cells: Vec<u8>,
+    colors: u8,
+    states: u8,
+    // indexed by state * colors + color
+    table: Vec<Transition>,
+    ants: Vec<Ant>,
+    boundary: Boundary,
+    generation: u64,
+}
+#[wasm_bindgen]
+impl Turmites {
+    fn get_index(&self, row: u32, column: u32) -> usize {
+        (row * self.width + column) as usize
+    }
+    // Langton's ant on an empty grid until told otherwise.
+    pub fn new(width: u32, height: u32) -> Turmites {
+        let mut turmites = Turmites {
+            width,
+            height,
+            cells: vec![0; (width * height) as usize],
+            colors: 0,
+            states: 0,
+            table: Vec::new(),
+            ants: Vec::new(),
+            boundary: Boundary::default(),
+            generation: 0,
+        };
+        let (colors, table) = parse_ant_rule("RL").expect("Langton's ant is a valid rule");
+        turmites.install(1, colors, table);
+        turmites
+    }
+    pub fn tick(&mut self) {
+        for i in 0..self.ants.len() {
+            let ant = self.ants[i];
+            let idx = self.get_index(ant.row, ant.column);
+            let entry = ant.state as usize * self.colors as usize + self.cells[idx] as usize;
+            let transition = self.table[entry];
+            self.cells[idx] = transition.write;
+            let direction = ant.direction.turn(transition.turn);
+            let (delta_row, delta_col) = direction.delta();
+            let ant = &mut self.ants[i];
+            ant.direction = direction;
+            ant.state = transition.next_state;
+            // an ant that would walk off a dead edge stays where it is
+            if let (Some(row), Some(column)) = (
+                self.boundary
+                    .resolve(ant.row as i64 + delta_row, self.height),
+                self.boundary
+                    .resolve(ant.column as i64 + delta_col, self.width),
+            ) {
+                ant.row = row;
+                ant.column = column;
+            }
+        }
+        self.generation += 1;
+    }
+    pub fn tick_n(&mut self, n: u32) {
+        for _ in 0..n {
+            self.tick();
+        }
+    }
+    // Multi-color ant in the usual turn-per-color notation, e.g. "RL" for
+    // Langton's ant or "LLRR": on color i the ant turns as the i-th letter
+    // says (L, R, N for no turn, U for a U-turn) and paints the next color.
+    pub fn set_ant_rule(&mut self, rule: &str) -> Result<(), JsError> {
+        let (colors, table) = parse_ant_rule(rule)?;
+        self.install(1, colors, table);
+        Ok(())
+    }
+    // General turmite: `table` holds (write color, clockwise quarter turns,
+    // next state) triples for every state and, within it, every color.
+    pub fn set_table(&mut self, states: u8, colors: u8, table: &[u8]) -> Result<(), JsError> {
+        let table = parse_table(states, colors, table)?;
+        self.install(states, colors, table);
+        Ok(())
+    }
+    fn install(&mut self, states: u8, colors: u8, table: Vec<Transition>) {
+        self.states = states;
+        self.colors = colors;
+        self.table = table;
+        for cell in self.cells.iter_mut().filter(|cell| **cell >= colors) {
+            *cell = 0;
+        }
+        for ant in self.ants.iter_mut() {
+            ant.state = 0;
+        }
+    }
+    pub fn add_ant(&mut self, row: u32, column: u32, direction: Direction) {
+        self.ants.push(Ant {
+            row: row.min(self.height.saturating_sub(1)),
+            column: column.min(self.width.saturating_sub(1)),
+            direction,
+            state: 0,
+        });
+    }
+    pub fn clear_ants(&mut self) {
+        self.ants.clear();
+    }
+    pub fn ant_count(&self) -> u32 {
+        self.ants.len() as u32
+    }
+    // Interleaved row, column pairs, in the order the ants were added.
+    pub fn ant_positions(&self) -> Vec<u32> {
+        self.ants
+            .iter()
+            .flat_map(|ant| [ant.row, ant.column])
+            .collect()
+    }
+    // undefined for an ant past ant_count()
+    pub fn ant_direction(&self, ant: u32) -> Option<Direction> {
+        self.ants.get(ant as usize).map(|ant| ant.direction)
+    }
+    // undefined for an ant past ant_count()
+    pub fn ant_state(&self, ant: u32) -> Option<u8> {
+        self.ants.get(ant as usize).map(|ant| ant.state)
+    }
+    pub fn clear(&mut self) {
+        self.cells.iter_mut().for_each(|c| *c = 0);
+        self.generation = 0;
+    }
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    pub fn colors(&self) -> u8 {
+        self.colors
+    }
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+    }
+    pub fn get_color(&self, row: u32, column: u32) -> u8 {
+        self.cells[self.get_index(row, column)]
+    }
+    pub fn set_color(&mut self, row: u32, column: u32, color: u8) {
+        let idx = self.get_index(row, column);
+        self.cells[idx] = color % self.colors;
+    }
+    pub fn cells(&self) -> js_sys::Uint8Array {
+        unsafe { js_sys::Uint8Array::view(&self.cells) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_with_more_than_255_entries() {
+        let (states, colors) = (16u8, 17u8);
+        let mut table = Vec::new();
+        for state in 0..states {
+            for color in 0..colors {
+                table.extend_from_slice(&[(color + 1) % colors, 1, (state + 1) % states]);
+            }
+        }
+        // the last entry, 15 * 17 + 16 = 271, writes color 5 and goes to state 3
+        let last = table.len() - 3;
+        table[last..].copy_from_slice(&[5, 0, 3]);
+        let mut turmites = Turmites::new(5, 5);
+        turmites.set_table(states, colors, &table).unwrap();
+        turmites.add_ant(2, 2, Direction::North);
+        turmites.ants[0].state = 15;
+        turmites.set_color(2, 2, 16);
+        turmites.tick();
+        assert_eq!(turmites.get_color(2, 2), 5);
+        assert_eq!(turmites.ant_state(0), Some(3));
+        assert_eq!(turmites.ant_positions(), vec![1, 2]);
+    }
+}
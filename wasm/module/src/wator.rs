@@ -0,0 +1,220 @@
+use crate::neighborhood::neighbor_indices;
+use crate::rng::Rng;
+use crate::Boundary;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WaTorCell {
+    Water = 0,
+    Fish = 1,
+    Shark = 2,
+}
+impl WaTorCell {
+    fn from_state(state: u8) -> WaTorCell {
+        match state {
+            1 => WaTorCell::Fish,
+            2 => WaTorCell::Shark,
+            _ => WaTorCell::Water,
+        }
+    }
+}
+const MOVES: [(i64, i64); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+
+// Dewdney's Wa-Tor: fish and sharks wander to a random free neighboring cell,
+// sharks eat a neighboring fish when they can, both breed once they have
+// survived their breed time, and sharks starve if they go too long unfed.
+#[wasm_bindgen]
+pub struct WaTor {
+    width: u32,
+    height: u32,
+    cells: Vec<u8>,
+    // ticks since the creature in the cell was born or last bred
+    ages: Vec<u16>,
+    // ticks since the shark in the cell last ate
+    hunger: Vec<u16>,
+    fish_breed: u16,
+    shark_breed: u16,
+    shark_starve: u16,
+    fish: u32,
+    sharks: u32,
+    boundary: Boundary,
+    rng: Rng,
+    generation: u64,
+}
+#[wasm_bindgen]
+impl WaTor {
+    fn pick(&mut self, idx: usize, creature: WaTorCell) -> Option<usize> {
+        let (row, col) = (idx as u32 / self.width, idx as u32 % self.width);
+        let candidates: Vec<usize> =
+            neighbor_indices(&MOVES, self.boundary, row, col, self.width, self.height)
+                .filter(|&neighbor| self.cells[neighbor] == creature as u8)
+                .collect();
+        if candidates.is_empty() {
+            None
+        } else {
+            Some(candidates[(self.rng.next_u64() % candidates.len() as u64) as usize])
+        }
+    }
+    // Moves the creature at `from` to `to`, leaving a newborn behind when it
+    // is old enough to breed.
+    fn relocate(&mut self, from: usize, to: usize, breed: u16) {
+        self.cells[to] = self.cells[from];
+        self.hunger[to] = self.hunger[from];
+        if self.ages[from] >= breed {
+            self.ages[to] = 0;
+            self.ages[from] = 0;
+            self.hunger[from] = 0;
+            match WaTorCell::from_state(self.cells[from]) {
+                WaTorCell::Fish => self.fish += 1,
+                WaTorCell::Shark => self.sharks += 1,
+                WaTorCell::Water => {}
+            }
+        } else {
+            self.ages[to] = self.ages[from];
+            self.cells[from] = WaTorCell::Water as u8;
+        }
+    }
+    pub fn new(width: u32, height: u32, seed: u64) -> WaTor {
+        let size = (width * height) as usize;
+        let mut wator = WaTor {
+            width,
+            height,
+            cells: vec![WaTorCell::Water as u8; size],
+            ages: vec![0; size],
+            hunger: vec![0; size],
+            fish_breed: 3,
+            shark_breed: 10,
+            shark_starve: 3,
+            fish: 0,
+            sharks: 0,
+            boundary: Boundary::default(),
+            rng: Rng::new(seed),
+            generation: 0,
+        };
+        wator.populate(0.3, 0.05);
+        wator
+    }
+    // Creatures move in a random order each tick, and each moves at most once.
+    pub fn tick(&mut self) {
+        let mut order: Vec<usize> = (0..self.cells.len()).collect();
+        for i in (1..order.len()).rev() {
+            let j = (self.rng.next_u64() % (i as u64 + 1)) as usize;
+            order.swap(i, j);
+        }
+        let mut moved = vec![false; self.cells.len()];
+        for idx in order {
+            if moved[idx] {
+                continue;
+            }
+            match WaTorCell::from_state(self.cells[idx]) {
+                WaTorCell::Water => {}
+                WaTorCell::Fish => {
+                    self.ages[idx] += 1;
+                    if let Some(to) = self.pick(idx, WaTorCell::Water) {
+                        self.relocate(idx, to, self.fish_breed);
+                        moved[to] = true;
+                    }
+                }
+                WaTorCell::Shark => {
+                    self.ages[idx] += 1;
+                    self.hunger[idx] += 1;
+                    let to = match self.pick(idx, WaTorCell::Fish) {
+                        Some(prey) => {
+                            self.fish -= 1;
+                            self.hunger[idx] = 0;
+                            Some(prey)
+                        }
+                        None => self.pick(idx, WaTorCell::Water),
+                    };
+                    let at = match to {
+                        Some(to) => {
+                            self.relocate(idx, to, self.shark_breed);
+                            moved[to] = true;
+                            to
+                        }
+                        None => idx,
+                    };
+                    if self.hunger[at] >= self.shark_starve {
+                        self.cells[at] = WaTorCell::Water as u8;
+                        self.sharks -= 1;
+                    }
+                }
+            }
+        }
+        self.generation += 1;
+    }
+    pub fn tick_n(&mut self, n: u32) {
+        for _ in 0..n {
+            self.tick();
+        }
+    }
+    // Restocks the ocean with the given fraction of fish and sharks.
+    pub fn populate(&mut self, fish_density: f32, shark_density: f32) {
+        self.fish = 0;
+        self.sharks = 0;
+        for idx in 0..self.cells.len() {
+            let roll = self.rng.next_f32();
+            let cell = if roll < shark_density {
+                self.sharks += 1;
+                WaTorCell::Shark
+            } else if roll < shark_density + fish_density {
+                self.fish += 1;
+                WaTorCell::Fish
+            } else {
+                WaTorCell::Water
+            };
+            self.cells[idx] = cell as u8;
+            // staggered ages keep the first generations from breeding in sync
+            self.ages[idx] = (self.rng.next_u64() % (self.fish_breed as u64 + 1)) as u16;
+            self.hunger[idx] = 0;
+        }
+        self.generation = 0;
+    }
+    pub fn fish_count(&self) -> u32 {
+        self.fish
+    }
+    pub fn shark_count(&self) -> u32 {
+        self.sharks
+    }
+    pub fn fish_breed(&self) -> u16 {
+        self.fish_breed
+    }
+    pub fn set_fish_breed(&mut self, ticks: u16) {
+        self.fish_breed = ticks.max(1);
+    }
+    pub fn shark_breed(&self) -> u16 {
+        self.shark_breed
+    }
+    pub fn set_shark_breed(&mut self, ticks: u16) {
+        self.shark_breed = ticks.max(1);
+    }
+    pub fn shark_starve(&self) -> u16 {
+        self.shark_starve
+    }
+    pub fn set_shark_starve(&mut self, ticks: u16) {
+        self.shark_starve = ticks.max(1);
+    }
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+    }
+    pub fn get_cell(&self, row: u32, column: u32) -> WaTorCell {
+        WaTorCell::from_state(self.cells[(row * self.width + column) as usize])
+    }
+    pub fn cells(&self) -> js_sys::Uint8Array {
+        unsafe { js_sys::Uint8Array::view(&self.cells) }
+    }
+}
@@ -0,0 +1,108 @@
+use crate::neighborhood::moore_neighbors;
+use crate::Boundary;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WireworldCell {
+    Empty = 0,
+    Conductor = 1,
+    Head = 2,
+    Tail = 3,
+}
+impl WireworldCell {
+    fn from_state(state: u8) -> WireworldCell {
+        match state {
+            1 => WireworldCell::Conductor,
+            2 => WireworldCell::Head,
+            3 => WireworldCell::Tail,
+            _ => WireworldCell::Empty,
+        }
+    }
+}
+#[wasm_bindgen]
+pub struct Wireworld {
+    width: u32,
+    height: u32,
+    cells: Vec<u8>,
+    boundary: Boundary,
+    generation: u64,
+}
+#[wasm_bindgen]
+impl Wireworld {
+    fn get_index(&self, row: u32, column: u32) -> usize {
+        (row * self.width + column) as usize
+    }
+    fn head_count(&self, row: u32, column: u32) -> u8 {
+        moore_neighbors(self.boundary, row, column, self.width, self.height)
+            .filter(|&idx| self.cells[idx] == WireworldCell::Head as u8)
+            .count() as u8
+    }
+    pub fn new(width: u32, height: u32) -> Wireworld {
+        Wireworld {
+            width,
+            height,
+            cells: vec![WireworldCell::Empty as u8; (width * height) as usize],
+            // circuits are laid out on a board, not a torus
+            boundary: Boundary::Dead,
+            generation: 0,
+        }
+    }
+    pub fn tick(&mut self) {
+        let mut next = self.cells.clone();
+        for row in 0..self.height {
+            for col in 0..self.width {
+                let idx = self.get_index(row, col);
+                let next_cell = match WireworldCell::from_state(self.cells[idx]) {
+                    WireworldCell::Empty => WireworldCell::Empty,
+                    WireworldCell::Head => WireworldCell::Tail,
+                    WireworldCell::Tail => WireworldCell::Conductor,
+                    WireworldCell::Conductor => match self.head_count(row, col) {
+                        1 | 2 => WireworldCell::Head,
+                        _ => WireworldCell::Conductor,
+                    },
+                };
+                next[idx] = next_cell as u8;
+            }
+        }
+        self.cells = next;
+        self.generation += 1;
+    }
+    pub fn tick_n(&mut self, n: u32) {
+        for _ in 0..n {
+            self.tick();
+        }
+    }
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+    }
+    pub fn get_cell(&self, row: u32, column: u32) -> WireworldCell {
+        WireworldCell::from_state(self.cells[self.get_index(row, column)])
+    }
+    pub fn set_cell(&mut self, row: u32, column: u32, cell: WireworldCell) {
+        let idx = self.get_index(row, column);
+        self.cells[idx] = cell as u8;
+    }
+    pub fn clear(&mut self) {
+        self.cells
+            .iter_mut()
+            .for_each(|c| *c = WireworldCell::Empty as u8);
+        self.generation = 0;
+    }
+    pub fn cells(&self) -> js_sys::Uint8Array {
+        unsafe { js_sys::Uint8Array::view(&self.cells) }
+    }
+}